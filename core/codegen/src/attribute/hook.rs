@@ -5,6 +5,10 @@ use proc_macro2::{Ident, TokenStream as TokenStream2};
 
 use {
     PARAM_PREFIX,
+    BEFORE_ALL_HOOK_FN_PREFIX,
+    BEFORE_ALL_HOOK_STRUCT_PREFIX,
+    BEFORE_FEATURE_HOOK_FN_PREFIX,
+    BEFORE_FEATURE_HOOK_STRUCT_PREFIX,
     BEFORE_SCENARIO_HOOK_FN_PREFIX,
     BEFORE_SCENARIO_HOOK_STRUCT_PREFIX,
     BEFORE_STEP_HOOK_FN_PREFIX,
@@ -13,6 +17,10 @@ use {
     AFTER_STEP_HOOK_STRUCT_PREFIX,
     AFTER_SCENARIO_HOOK_FN_PREFIX,
     AFTER_SCENARIO_HOOK_STRUCT_PREFIX,
+    AFTER_FEATURE_HOOK_FN_PREFIX,
+    AFTER_FEATURE_HOOK_STRUCT_PREFIX,
+    AFTER_ALL_HOOK_FN_PREFIX,
+    AFTER_ALL_HOOK_STRUCT_PREFIX,
 };
 use glue_codegen::{HookType, TagExpression};
 use proc_macro_ext::{Diagnostics, StringLit};
@@ -53,6 +61,15 @@ fn parse_hook(attr: HookAttribute, function: syn::ItemFn) -> Result<Hook> {
     // Gather diagnostics as we proceed.
     let mut diags = Diagnostics::new();
 
+    let global_hook = match attr.hook_type.value.0 {
+        glue::hook::HookType::BeforeAll | glue::hook::HookType::AfterAll => true,
+        _ => false,
+    };
+    if global_hook && attr.tag_expression.is_some() {
+        diags.push(attr.hook_type.span.error("tag expressions are not supported for this hook type")
+            .help("`BeforeAll` and `AfterAll` hooks run only once for the whole test run"));
+    }
+
     // Check the validity of function arguments.
     let mut inputs = vec![];
     for input in &function.decl.inputs {
@@ -101,10 +118,14 @@ fn generate_fn_name(user_handler_fn_name: &Ident, hook_type: &HookType) -> Ident
     use glue::hook::HookType::*;
 
     let hook_fn_prefix = match hook_type.0 {
+        BeforeAll => BEFORE_ALL_HOOK_FN_PREFIX,
+        BeforeFeature => BEFORE_FEATURE_HOOK_FN_PREFIX,
         BeforeScenario => BEFORE_SCENARIO_HOOK_FN_PREFIX,
         BeforeStep => BEFORE_STEP_HOOK_FN_PREFIX,
         AfterStep => AFTER_STEP_HOOK_FN_PREFIX,
         AfterScenario => AFTER_SCENARIO_HOOK_FN_PREFIX,
+        AfterFeature => AFTER_FEATURE_HOOK_FN_PREFIX,
+        AfterAll => AFTER_ALL_HOOK_FN_PREFIX,
     };
 
     user_handler_fn_name.prepend(hook_fn_prefix)
@@ -114,10 +135,14 @@ fn generate_struct_name(user_handler_fn_name: &Ident, hook_type: &HookType) -> I
     use glue::hook::HookType::*;
 
     let hook_struct_prefix = match hook_type.0 {
+        BeforeAll => BEFORE_ALL_HOOK_STRUCT_PREFIX,
+        BeforeFeature => BEFORE_FEATURE_HOOK_STRUCT_PREFIX,
         BeforeScenario => BEFORE_SCENARIO_HOOK_STRUCT_PREFIX,
         BeforeStep => BEFORE_STEP_HOOK_STRUCT_PREFIX,
         AfterStep => AFTER_STEP_HOOK_STRUCT_PREFIX,
         AfterScenario => AFTER_SCENARIO_HOOK_STRUCT_PREFIX,
+        AfterFeature => AFTER_FEATURE_HOOK_STRUCT_PREFIX,
+        AfterAll => AFTER_ALL_HOOK_STRUCT_PREFIX,
     };
 
     user_handler_fn_name.prepend(hook_struct_prefix)
@@ -161,10 +186,11 @@ fn codegen_hook(hook: Hook) -> Result<TokenStream> {
 
             #(#data_statements)*
 
-            // TODO: error handling...
             let result = ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| #user_handler_fn_name(#(#parameter_names),*)));
             match result {
-                Ok(user_handler_fn_result) => return Ok(()),
+                Ok(user_handler_fn_result) => {
+                    return ::cuke_runner::glue::error::IntoExecutionResult::into_execution_result(user_handler_fn_result)
+                },
                 Err(err) => return Err(::cuke_runner::glue::error::panic_error(err)),
             };
         }
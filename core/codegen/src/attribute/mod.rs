@@ -1,2 +1,3 @@
 pub mod hook;
+pub mod parameter_type;
 pub mod step;
@@ -0,0 +1,178 @@
+use proc_macro::{Span, TokenStream};
+
+use devise::{FromMeta, Result, Spanned, SpanWrapped, syn};
+use proc_macro2::TokenStream as TokenStream2;
+
+use {PARAMETER_TYPE_FN_PREFIX, PARAMETER_TYPE_STRUCT_PREFIX};
+use glue::step::cucumber_expression;
+use glue_codegen::Regex;
+use syn_ext::{IdentExt, ReturnTypeExt, syn_to_diag};
+
+use self::syn::{Attribute, parse::Parser};
+
+/// The raw, parsed `#[parameter_type]` attribute.
+#[derive(Debug, FromMeta)]
+struct ParameterTypeAttribute {
+    name: Option<SpanWrapped<String>>,
+    regex: Regex,
+}
+
+/// This structure represents the parsed `parameter_type` attribute and associated items.
+#[derive(Debug)]
+struct ParameterType {
+    /// The name of the parameter type as used in cucumber expressions.
+    name: String,
+    /// The regex that matches the text of the parameter.
+    regex: Regex,
+    /// The function that was decorated with the `parameter_type` attribute.
+    function: syn::ItemFn,
+    /// The type that the matched text is converted to.
+    target_type: syn::Type,
+    /// Whether the function returns a `Result` of the `target_type`.
+    fallible: bool,
+}
+
+fn parse_parameter_type(attr: ParameterTypeAttribute, function: syn::ItemFn) -> Result<ParameterType> {
+    let (name, name_span) = match attr.name {
+        Some(name) => (name.value, name.span),
+        None => (function.ident.to_string(), function.ident.span().unstable()),
+    };
+
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(name_span.error(format!("invalid parameter type name \"{}\"", name))
+            .help("parameter type names must not be empty and may only contain \
+                ASCII letters, digits and `_`"));
+    }
+    if cucumber_expression::builtin_parameter_type_regex(&name).is_some() {
+        return Err(name_span.error(format!("parameter type name \"{}\" is reserved for a built-in parameter type", name)));
+    }
+
+    if function.decl.inputs.len() != 1 {
+        return Err(function.decl.inputs.span()
+            .error("parameter type functions must have exactly one argument")
+            .help("the argument is the matched text of the parameter, for example: `text: &str`"));
+    }
+
+    let return_type = match function.decl.output.ty() {
+        Some(return_type) => return_type.clone(),
+        None => return Err(function.ident.span().unstable()
+            .error("parameter type functions must return the converted value")
+            .help("return either the value itself or a `Result` of the value")),
+    };
+    let (target_type, fallible) = match result_ok_type(&return_type) {
+        Some(ok_type) => (ok_type, true),
+        None => (return_type, false),
+    };
+
+    Ok(ParameterType { name, regex: attr.regex, function, target_type, fallible })
+}
+
+/// Returns the `T` of a `Result<T, E>` type.
+fn result_ok_type(ty: &syn::Type) -> Option<syn::Type> {
+    let type_path = match ty {
+        syn::Type::Path(type_path) => type_path,
+        _ => return None,
+    };
+
+    let last_segment = type_path.path.segments.iter().last()?;
+    if last_segment.ident != "Result" {
+        return None;
+    }
+
+    match last_segment.arguments {
+        syn::PathArguments::AngleBracketed(ref arguments) => {
+            match arguments.args.iter().next() {
+                Some(syn::GenericArgument::Type(ok_type)) => Some(ok_type.clone()),
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+fn codegen_parameter_type(parameter_type: ParameterType) -> Result<TokenStream> {
+    // Gather everything we need.
+    let (vis, user_handler_fn) = (&parameter_type.function.vis, &parameter_type.function);
+    let user_handler_fn_name = &user_handler_fn.ident;
+    let user_handler_fn_span = &user_handler_fn.ident.span().unstable();
+    let user_handler_fn_path = {
+        let source_file_path = user_handler_fn_span.source_file().path();
+        match source_file_path.canonicalize() {
+            Ok(canonicalized_path) => canonicalized_path,
+            Err(_) => source_file_path,
+        }
+    };
+    let user_handler_fn_file_path = user_handler_fn_path.to_string_lossy().to_owned();
+    let user_handler_fn_line_number = user_handler_fn_span.start().line;
+    let generated_struct_name = user_handler_fn_name.prepend(PARAMETER_TYPE_STRUCT_PREFIX);
+    let name = &parameter_type.name;
+    let generated_fn_name = syn::Ident::new(&format!("{}{}", PARAMETER_TYPE_FN_PREFIX, name),
+        user_handler_fn.ident.span());
+    let regex = &parameter_type.regex;
+    let target_type = &parameter_type.target_type;
+
+    let data_table_error = format!("cannot convert DataTable to parameter type {{{}}}", name);
+    let conversion = if parameter_type.fallible {
+        quote! {
+            #user_handler_fn_name(value).map_err(|err|
+                ::cuke_runner::glue::step::argument::FromStepArgumentError::new(format!("{:?}", err)))
+        }
+    } else {
+        quote!(Ok(#user_handler_fn_name(value)))
+    };
+
+    Ok(quote! {
+        #user_handler_fn
+
+        /// Cuke runner code generated step argument conversion.
+        ///
+        /// Step functions call it by name for the `{name}` parameters of their expression.
+        #[allow(non_snake_case)]
+        #vis fn #generated_fn_name(step_argument: &::cuke_runner::glue::step::argument::StepArgument)
+            -> ::cuke_runner::glue::step::argument::FromStepArgumentResult<#target_type>
+        {
+            use ::cuke_runner::glue::step::argument::StepArgument::*;
+
+            let value = match step_argument {
+                Expression(ref expression) => expression.value(),
+                DocString(ref doc_string) => doc_string.value(),
+                DataTable(ref _data_table) => return Err(
+                    ::cuke_runner::glue::step::argument::FromStepArgumentError::new(#data_table_error)
+                ),
+            };
+
+            #conversion
+        }
+
+        /// Cuke runner code generated static parameter type info.
+        #[allow(non_upper_case_globals)]
+        #vis static #generated_struct_name: ::cuke_runner::glue::parameter_type::StaticParameterTypeDef =
+            ::cuke_runner::glue::parameter_type::StaticParameterTypeDef {
+                name: #name,
+                regex: #regex,
+                location: ::cuke_runner::glue::CodeLocation {
+                    file_path: #user_handler_fn_file_path,
+                    line_number: #user_handler_fn_line_number,
+                },
+            };
+    }.into())
+}
+
+fn complete_parameter_type(args: TokenStream2, input: TokenStream) -> Result<TokenStream> {
+    let function: syn::ItemFn = syn::parse(input).map_err(syn_to_diag)
+        .map_err(|diag| diag.help("`#[parameter_type]` can only be used on functions"))?;
+
+    let full_attr = quote!(#[parameter_type(#args)]);
+    let attrs = Attribute::parse_outer.parse2(full_attr).map_err(syn_to_diag)?;
+    let attribute = match ParameterTypeAttribute::from_attrs("parameter_type", &attrs) {
+        Some(result) => result?,
+        None => return Err(Span::call_site().error("internal error: bad attribute"))
+    };
+
+    codegen_parameter_type(parse_parameter_type(attribute, function)?)
+}
+
+pub fn parameter_type_attribute(args: TokenStream, input: TokenStream) -> TokenStream {
+    complete_parameter_type(args.into(), input)
+        .unwrap_or_else(|diag| { diag.emit(); TokenStream::new() })
+}
@@ -3,8 +3,9 @@ use proc_macro::{Span, TokenStream};
 use devise::{ext::TypeExt, FromMeta, Result, Spanned, SpanWrapped, syn};
 use proc_macro2::TokenStream as TokenStream2;
 
-use {PARAM_PREFIX, STEP_FN_PREFIX, STEP_STRUCT_PREFIX};
-use glue_codegen::{StepKeyword, Regex};
+use {PARAM_PREFIX, PARAMETER_TYPE_FN_PREFIX, STEP_FN_PREFIX, STEP_STRUCT_PREFIX};
+use glue::step::cucumber_expression;
+use glue_codegen::{StepKeyword, StepExpression};
 use proc_macro_ext::{Diagnostics, StringLit};
 use syn_ext::{IdentExt, syn_to_diag};
 
@@ -15,14 +16,14 @@ use self::syn::{Attribute, parse::Parser};
 struct StepAttribute {
     #[meta(naked)]
     keyword: SpanWrapped<StepKeyword>,
-    expression: SpanWrapped<Regex>,
+    expression: SpanWrapped<StepExpression>,
 }
 
 /// The raw, parsed `#[step]` (e.g, `given`, `when`, `then`) attribute.
 #[derive(Debug, FromMeta)]
 struct KeywordStepAttribute {
     #[meta(naked)]
-    expression: SpanWrapped<Regex>,
+    expression: SpanWrapped<StepExpression>,
 }
 
 /// This structure represents the parsed `step` attribute and associated items.
@@ -130,6 +131,28 @@ fn step_data_expr(ident: &syn::Ident, ty: &syn::Type, step_argument_index: usize
     }
 }
 
+/// Converts the step argument with the generated function of a user defined parameter type.
+///
+/// The function is resolved by the name of the parameter type, so referencing an undefined
+/// parameter type (or one that is not in scope) is a compile error at the step expression.
+fn parameter_type_data_expr(
+    ident: &syn::Ident,
+    ty: &syn::Type,
+    step_argument_index: usize,
+    conversion_fn_name: &syn::Ident,
+) -> TokenStream2 {
+    let span = ident.span().unstable().join(ty.span()).unwrap().into();
+    quote_spanned! { span =>
+        #[allow(non_snake_case, unreachable_patterns)]
+        let #ident: #ty = match #conversion_fn_name(&__step_arguments[#step_argument_index]) {
+            Ok(step_argument) => step_argument,
+            Err(error) => {
+                return Err(::cuke_runner::glue::error::ExecutionError::from(error))
+            },
+        };
+    }
+}
+
 fn codegen_step(step: Step) -> Result<TokenStream> {
     // Gather everything we need.
     let (vis, user_handler_fn) = (&step.function.vis, &step.function);
@@ -149,6 +172,13 @@ fn codegen_step(step: Step) -> Result<TokenStream> {
     let parameter_names = step.inputs.iter().map(|(_, cuke_runner_ident, _)| cuke_runner_ident);
     let keyword = step.attribute.keyword;
     let expression = step.attribute.expression;
+    let expression_span = expression.span.into();
+    let conversion_fn_names: Vec<Option<syn::Ident>> = expression.1.iter()
+        .map(|name| match cucumber_expression::builtin_parameter_type_regex(name) {
+            Some(_) => None,
+            None => Some(syn::Ident::new(&format!("{}{}", PARAMETER_TYPE_FN_PREFIX, name), expression_span)),
+        })
+        .collect();
 
     let mut data_statements = Vec::with_capacity(step.inputs.len());
     let mut first = true;
@@ -158,10 +188,19 @@ fn codegen_step(step: Step) -> Result<TokenStream> {
             first = false;
         } else {
             let step_argument_index = index - 1;
-            data_statements.push(step_data_expr(cuke_runner_ident, &ty, step_argument_index));
+            match conversion_fn_names.get(step_argument_index) {
+                Some(Some(conversion_fn_name)) => data_statements.push(
+                    parameter_type_data_expr(cuke_runner_ident, &ty, step_argument_index, conversion_fn_name)),
+                _ => data_statements.push(step_data_expr(cuke_runner_ident, &ty, step_argument_index)),
+            }
         }
     }
 
+    // Parameter types without a matching function argument must exist nevertheless.
+    let unused_conversion_fn_names = conversion_fn_names.iter()
+        .skip(step.inputs.len().saturating_sub(1))
+        .filter_map(Option::as_ref);
+
     Ok(quote! {
         #user_handler_fn
 
@@ -171,12 +210,15 @@ fn codegen_step(step: Step) -> Result<TokenStream> {
             __step_arguments: &[::cuke_runner::glue::step::argument::StepArgument],
         ) -> ::std::result::Result<(), ::cuke_runner::glue::error::ExecutionError> {
 
+            #(let _ = #unused_conversion_fn_names;
+            )*
             #(#data_statements)*
 
-            // TODO: error handling...
             let result = ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| #user_handler_fn_name(#(#parameter_names),*)));
             match result {
-                Ok(user_handler_fn_result) => return Ok(()),
+                Ok(user_handler_fn_result) => {
+                    return ::cuke_runner::glue::error::IntoExecutionResult::into_execution_result(user_handler_fn_result)
+                },
                 Err(err) => return Err(::cuke_runner::glue::error::panic_error(err)),
             };
         }
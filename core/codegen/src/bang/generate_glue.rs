@@ -7,11 +7,16 @@ use proc_macro::TokenStream;
 use syn::{self, Path, Attribute, Item, Visibility};
 use devise::Result;
 use {
+    PARAMETER_TYPE_STRUCT_PREFIX,
+    BEFORE_ALL_HOOK_STRUCT_PREFIX,
+    BEFORE_FEATURE_HOOK_STRUCT_PREFIX,
     BEFORE_SCENARIO_HOOK_STRUCT_PREFIX,
     BEFORE_STEP_HOOK_STRUCT_PREFIX,
     STEP_STRUCT_PREFIX,
     AFTER_STEP_HOOK_STRUCT_PREFIX,
     AFTER_SCENARIO_HOOK_STRUCT_PREFIX,
+    AFTER_FEATURE_HOOK_STRUCT_PREFIX,
+    AFTER_ALL_HOOK_STRUCT_PREFIX,
 };
 
 crate fn generate_glue_macro(_input: TokenStream) -> Result<TokenStream> {
@@ -24,6 +29,12 @@ crate fn generate_glue_macro(_input: TokenStream) -> Result<TokenStream> {
 
     debug!("current_file_path: {}", current_file_path.display());
 
+    let parameter_type_definition_path_tokens: Vec<Path> = parse_paths(&current_file_path,
+        &["parameter_type"], PARAMETER_TYPE_STRUCT_PREFIX);
+    let before_all_hook_definition_path_tokens: Vec<Path> = parse_paths(&current_file_path,
+        &["before_all"], BEFORE_ALL_HOOK_STRUCT_PREFIX);
+    let before_feature_hook_definition_path_tokens: Vec<Path> = parse_paths(&current_file_path,
+        &["before_feature"], BEFORE_FEATURE_HOOK_STRUCT_PREFIX);
     let before_scenario_hook_definition_path_tokens: Vec<Path> = parse_paths(&current_file_path,
         &["before_scenario"], BEFORE_SCENARIO_HOOK_STRUCT_PREFIX);
     let before_step_hook_definition_path_tokens: Vec<Path> = parse_paths(&current_file_path,
@@ -34,9 +45,25 @@ crate fn generate_glue_macro(_input: TokenStream) -> Result<TokenStream> {
         &["after_step"], AFTER_STEP_HOOK_STRUCT_PREFIX);
     let after_scenario_hook_definition_path_tokens: Vec<Path> = parse_paths(&current_file_path,
         &["after_scenario"], AFTER_SCENARIO_HOOK_STRUCT_PREFIX);
+    let after_feature_hook_definition_path_tokens: Vec<Path> = parse_paths(&current_file_path,
+        &["after_feature"], AFTER_FEATURE_HOOK_STRUCT_PREFIX);
+    let after_all_hook_definition_path_tokens: Vec<Path> = parse_paths(&current_file_path,
+        &["after_all"], AFTER_ALL_HOOK_STRUCT_PREFIX);
 
     let call_site_span = Span::call_site();
     let static_glue_definition_tokens = quote_spanned! {call_site_span=>
+        pub static PARAMETER_TYPE_DEFINITIONS: &[&::cuke_runner::glue::parameter_type::StaticParameterTypeDef] = &[
+            #(&#parameter_type_definition_path_tokens,
+            )*
+        ];
+        pub static BEFORE_ALL_HOOK_DEFINITIONS: &[&::cuke_runner::glue::hook::StaticHookDef] = &[
+            #(&#before_all_hook_definition_path_tokens,
+            )*
+        ];
+        pub static BEFORE_FEATURE_HOOK_DEFINITIONS: &[&::cuke_runner::glue::hook::StaticHookDef] = &[
+            #(&#before_feature_hook_definition_path_tokens,
+            )*
+        ];
         pub static BEFORE_SCENARIO_HOOK_DEFINITIONS: &[&::cuke_runner::glue::hook::StaticHookDef] = &[
             #(&#before_scenario_hook_definition_path_tokens,
             )*
@@ -57,6 +84,14 @@ crate fn generate_glue_macro(_input: TokenStream) -> Result<TokenStream> {
             #(&#after_scenario_hook_definition_path_tokens,
             )*
         ];
+        pub static AFTER_FEATURE_HOOK_DEFINITIONS: &[&::cuke_runner::glue::hook::StaticHookDef] = &[
+            #(&#after_feature_hook_definition_path_tokens,
+            )*
+        ];
+        pub static AFTER_ALL_HOOK_DEFINITIONS: &[&::cuke_runner::glue::hook::StaticHookDef] = &[
+            #(&#after_all_hook_definition_path_tokens,
+            )*
+        ];
     };
 
     Ok(TokenStream::from(static_glue_definition_tokens))
@@ -12,11 +12,16 @@ crate fn glue_macro(input: TokenStream) -> Result<TokenStream> {
     let static_glue_definitions = paths.into_iter()
         .map(|path| quote_spanned! {path.span().into()=>
             ::cuke_runner::glue::StaticGlueDefinitions {
+                parameter_types: #path::PARAMETER_TYPE_DEFINITIONS,
+                before_all_hooks: #path::BEFORE_ALL_HOOK_DEFINITIONS,
+                before_feature_hooks: #path::BEFORE_FEATURE_HOOK_DEFINITIONS,
                 before_scenario_hooks: #path::BEFORE_SCENARIO_HOOK_DEFINITIONS,
                 before_step_hooks: #path::BEFORE_STEP_HOOK_DEFINITIONS,
                 steps: #path::STEP_DEFINITIONS,
                 after_step_hooks: #path::AFTER_STEP_HOOK_DEFINITIONS,
                 after_scenario_hooks: #path::AFTER_SCENARIO_HOOK_DEFINITIONS,
+                after_feature_hooks: #path::AFTER_FEATURE_HOOK_DEFINITIONS,
+                after_all_hooks: #path::AFTER_ALL_HOOK_DEFINITIONS,
             }
         });
 
@@ -11,6 +11,11 @@ crate struct HookType(crate glue::hook::HookType);
 #[derive(Debug)]
 crate struct StepKeyword(crate glue::step::StepKeyword);
 
+/// A step expression together with the names of the parameter types
+/// it references (which are always empty for regexes).
+#[derive(Debug)]
+crate struct StepExpression(crate String, crate Vec<String>);
+
 #[derive(Debug)]
 crate struct Regex(crate regex::Regex);
 
@@ -26,13 +31,18 @@ impl FromMeta for StringLit {
     }
 }
 
-const VALID_HOOK_TYPES_STR: &str = "`BeforeScenario`, `BeforeStep`, `AfterStep`, `AfterScenario`";
+const VALID_HOOK_TYPES_STR: &str = "`BeforeAll`, `BeforeFeature`, `BeforeScenario`, `BeforeStep`, \
+    `AfterStep`, `AfterScenario`, `AfterFeature`, `AfterAll`";
 
 const VALID_HOOK_TYPES: &[glue::hook::HookType] = &[
+    glue::hook::HookType::BeforeAll,
+    glue::hook::HookType::BeforeFeature,
     glue::hook::HookType::BeforeScenario,
     glue::hook::HookType::BeforeStep,
     glue::hook::HookType::AfterStep,
     glue::hook::HookType::AfterScenario,
+    glue::hook::HookType::AfterFeature,
+    glue::hook::HookType::AfterAll,
 ];
 
 impl FromMeta for HookType {
@@ -61,10 +71,14 @@ impl ToTokens for HookType {
         use glue::hook::HookType::*;
 
         let keyword_tokens = match self.0 {
+            BeforeAll => quote!(::cuke_runner::glue::HookType::BeforeAll),
+            BeforeFeature => quote!(::cuke_runner::glue::HookType::BeforeFeature),
             BeforeScenario => quote!(::cuke_runner::glue::HookType::BeforeScenario),
             BeforeStep => quote!(::cuke_runner::glue::HookType::BeforeStep),
             AfterStep => quote!(::cuke_runner::glue::HookType::AfterStep),
             AfterScenario => quote!(::cuke_runner::glue::HookType::AfterScenario),
+            AfterFeature => quote!(::cuke_runner::glue::HookType::AfterFeature),
+            AfterAll => quote!(::cuke_runner::glue::HookType::AfterAll),
         };
 
         tokens.extend(keyword_tokens);
@@ -116,6 +130,58 @@ impl ToTokens for StepKeyword {
     }
 }
 
+const BUILTIN_PARAMETER_TYPES_STR: &str = "`{int}`, `{float}`, `{word}`, `{string}`, `{}`";
+
+impl FromMeta for StepExpression {
+    fn from_meta(meta: MetaItem) -> Result<Self> {
+        use glue::step::cucumber_expression::{self, CucumberExpression};
+
+        let string = StringLit::from_meta(meta)?;
+        let span = string.subspan(1..=string.len())
+            .unwrap_or_else(|| string.1.span());
+
+        if cucumber_expression::is_regex(&string) {
+            return match regex::Regex::new(&string) {
+                Ok(_regex) => Ok(StepExpression(string.to_owned(), Vec::new())),
+                Err(err) => Err(span.error(format!("step expression \"{}\" is not a valid regex: {}", &*string, err))),
+            };
+        }
+
+        if cucumber_expression::contains_regex_syntax(&string) {
+            return Err(span.error(format!("step expression \"{}\" looks like a regex, but is not anchored", &*string))
+                .help("regexes must start with `^` or end with `$`, \
+                    cucumber expressions use parameter types like `{int}` instead of `\\d+`"));
+        }
+
+        // User defined parameter types are resolved by name when the step function is generated.
+        let result = CucumberExpression::parse(&string);
+        match result {
+            Ok(expression) => {
+                let parameter_type_names = expression.parameter_type_names();
+                let invalid_name = parameter_type_names.iter()
+                    .find(|name| cucumber_expression::builtin_parameter_type_regex(name).is_none()
+                        && !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+                if let Some(name) = invalid_name {
+                    return Err(span.error(format!("step expression \"{}\" references the undefined parameter type {{{}}}", &*string, name))
+                        .help(format!("parameter types are either built-in ({}) or defined with `#[parameter_type]`", BUILTIN_PARAMETER_TYPES_STR)));
+                }
+
+                let parameter_type_names = parameter_type_names.into_iter().map(str::to_owned).collect();
+                Ok(StepExpression(string.to_owned(), parameter_type_names))
+            },
+            Err(err) => Err(span.error(format!("step expression \"{}\" is not a valid cucumber expression: {}", &*string, err))
+                .help("regexes must start with `^` or end with `$`")),
+        }
+    }
+}
+
+impl ToTokens for StepExpression {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let string = self.0.as_str();
+        tokens.extend(quote!(#string));
+    }
+}
+
 impl FromMeta for Regex {
     fn from_meta(meta: MetaItem) -> Result<Self> {
         let string = StringLit::from_meta(meta)?;
@@ -125,7 +191,7 @@ impl FromMeta for Regex {
         let result = regex::Regex::new(&string);
         match result {
             Ok(regex) => Ok(Regex(regex)),
-            Err(err) => Err(span.error(format!("step expression \"{}\" is not a valid regex: {}", &*string, err))),
+            Err(err) => Err(span.error(format!("\"{}\" is not a valid regex: {}", &*string, err))),
         }
     }
 }
@@ -29,6 +29,10 @@ mod bang;
 mod glue_codegen;
 mod syn_ext;
 
+crate static BEFORE_ALL_HOOK_STRUCT_PREFIX: &str = "static_cuke_runner_before_all_hook_info_for_";
+crate static BEFORE_ALL_HOOK_FN_PREFIX: &str = "cuke_runner_before_all_hook_fn_";
+crate static BEFORE_FEATURE_HOOK_STRUCT_PREFIX: &str = "static_cuke_runner_before_feature_hook_info_for_";
+crate static BEFORE_FEATURE_HOOK_FN_PREFIX: &str = "cuke_runner_before_feature_hook_fn_";
 crate static BEFORE_SCENARIO_HOOK_STRUCT_PREFIX: &str = "static_cuke_runner_before_scenario_hook_info_for_";
 crate static BEFORE_SCENARIO_HOOK_FN_PREFIX: &str = "cuke_runner_before_scenario_hook_fn_";
 crate static BEFORE_STEP_HOOK_STRUCT_PREFIX: &str = "static_cuke_runner_before_scenario_hook_info_for_";
@@ -37,10 +41,17 @@ crate static AFTER_STEP_HOOK_STRUCT_PREFIX: &str = "static_cuke_runner_before_sc
 crate static AFTER_STEP_HOOK_FN_PREFIX: &str = "cuke_runner_before_scenario_hook_fn_";
 crate static AFTER_SCENARIO_HOOK_STRUCT_PREFIX: &str = "static_cuke_runner_before_scenario_hook_info_for_";
 crate static AFTER_SCENARIO_HOOK_FN_PREFIX: &str = "cuke_runner_before_scenario_hook_fn_";
+crate static AFTER_FEATURE_HOOK_STRUCT_PREFIX: &str = "static_cuke_runner_after_feature_hook_info_for_";
+crate static AFTER_FEATURE_HOOK_FN_PREFIX: &str = "cuke_runner_after_feature_hook_fn_";
+crate static AFTER_ALL_HOOK_STRUCT_PREFIX: &str = "static_cuke_runner_after_all_hook_info_for_";
+crate static AFTER_ALL_HOOK_FN_PREFIX: &str = "cuke_runner_after_all_hook_fn_";
 
 crate static STEP_STRUCT_PREFIX: &str = "static_cuke_runner_step_info_for_";
 crate static STEP_FN_PREFIX: &str = "cuke_runner_step_fn_";
 
+crate static PARAMETER_TYPE_STRUCT_PREFIX: &str = "static_cuke_runner_parameter_type_info_for_";
+crate static PARAMETER_TYPE_FN_PREFIX: &str = "cuke_runner_parameter_type_fn_for_";
+
 crate static PARAM_PREFIX: &str = "__cuke_runner_param_";
 
 macro_rules! emit {
@@ -69,10 +80,14 @@ macro_rules! hook_attribute {
 }
 
 hook_attribute!(hook => None);
+hook_attribute!(before_all => HookType::BeforeAll);
+hook_attribute!(before_feature => HookType::BeforeFeature);
 hook_attribute!(before_scenario => HookType::BeforeScenario);
 hook_attribute!(before_step => HookType::BeforeStep);
 hook_attribute!(after_step => HookType::AfterStep);
 hook_attribute!(after_scenario => HookType::AfterScenario);
+hook_attribute!(after_feature => HookType::AfterFeature);
+hook_attribute!(after_all => HookType::AfterAll);
 
 macro_rules! step_attribute {
     ($name:ident => $keyword:expr) => (
@@ -88,6 +103,11 @@ step_attribute!(given => StepKeyword::Given);
 step_attribute!(when => StepKeyword::When);
 step_attribute!(then => StepKeyword::Then);
 
+#[proc_macro_attribute]
+pub fn parameter_type(args: TokenStream, input: TokenStream) -> TokenStream {
+    emit!(attribute::parameter_type::parameter_type_attribute(args, input))
+}
+
 
 #[proc_macro]
 pub fn generate_glue(input: TokenStream) -> TokenStream {
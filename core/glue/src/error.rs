@@ -9,11 +9,71 @@ pub enum ExecutionError {
     FromScenario(#[cause] FromScenarioError),
     FromStepArgument(#[cause] FromStepArgumentError),
     Panic(#[cause] PanicError),
+    /// The step or hook function is not implemented yet (see the `pending!` macro).
+    Pending(#[cause] PendingError),
     Other(#[cause] ::failure::Error),
 }
 
+/// Converts the return value of a user defined step or hook function into an execution result.
+///
+/// This is implemented for `()` and for `Result<(), E>` where `E` can be converted
+/// into a `failure::Error`, which includes every `std::error::Error + Send + Sync + 'static`.
+pub trait IntoExecutionResult {
+    fn into_execution_result(self) -> Result<(), ExecutionError>;
+}
+
+impl IntoExecutionResult for () {
+    fn into_execution_result(self) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+}
+
+impl<E: Into<::failure::Error>> IntoExecutionResult for Result<(), E> {
+    fn into_execution_result(self) -> Result<(), ExecutionError> {
+        self.map_err(|err| {
+            let error: ::failure::Error = err.into();
+
+            match error.downcast::<PendingError>() {
+                Ok(pending_error) => ExecutionError::Pending(pending_error),
+                Err(error) => ExecutionError::Other(error),
+            }
+        })
+    }
+}
+
 pub fn panic_error(error: Box<::std::any::Any + Send + 'static>) -> ExecutionError {
-    ExecutionError::Panic(PanicError { message: format!("{:?}", error) })
+    match error.downcast::<PendingError>() {
+        Ok(pending_error) => ExecutionError::Pending(*pending_error),
+        Err(error) => ExecutionError::Panic(PanicError { message: format!("{:?}", error) }),
+    }
+}
+
+/// Marks a step or hook function as not implemented yet.
+///
+/// Either raised with the `pending!` macro or returned as error of a step or hook function.
+#[derive(Fail, Debug)]
+pub struct PendingError {
+    message: String,
+}
+
+impl PendingError {
+    pub fn new<S: Into<String>>(message: S) -> PendingError {
+        PendingError {
+            message: message.into()
+        }
+    }
+}
+
+impl Default for PendingError {
+    fn default() -> PendingError {
+        PendingError::new("TODO: implement me")
+    }
+}
+
+impl fmt::Display for PendingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.message, f)
+    }
 }
 
 #[derive(Fail, Debug)]
@@ -45,6 +105,7 @@ impl fmt::Display for ExecutionError {
             ExecutionError::FromScenario(ref err) => fmt::Display::fmt(err, f),
             ExecutionError::FromStepArgument(ref err) => fmt::Display::fmt(err, f),
             ExecutionError::Panic(ref err) => fmt::Display::fmt(err, f),
+            ExecutionError::Pending(ref err) => fmt::Display::fmt(err, f),
             ExecutionError::Other(ref err) => fmt::Display::fmt(err, f),
         }
     }
@@ -53,6 +53,7 @@ impl fmt::Debug for StaticHookDef {
     fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
         f.debug_struct("StaticHookDef")
             .field("name", &self.name)
+            .field("order", &self.order)
             .field("tag_expression", &self.tag_expression)
             .field("hook_fn", &"<hook_fn>")
             .field("location", &self.location)
@@ -63,10 +64,14 @@ impl fmt::Debug for StaticHookDef {
 /// A hook type like "BeforeScenario" or "AfterStep".
 #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
 pub enum HookType {
+    BeforeAll,
+    BeforeFeature,
     BeforeScenario,
     BeforeStep,
     AfterStep,
     AfterScenario,
+    AfterFeature,
+    AfterAll,
 }
 
 impl HookType {
@@ -75,10 +80,14 @@ impl HookType {
         use self::HookType::*;
 
         match self {
+            BeforeAll => "BeforeAll",
+            BeforeFeature => "BeforeFeature",
             BeforeScenario => "BeforeScenario",
             BeforeStep => "BeforeStep",
             AfterStep => "AfterStep",
             AfterScenario => "AfterScenario",
+            AfterFeature => "AfterFeature",
+            AfterAll => "AfterAll",
         }
     }
 }
@@ -90,10 +99,14 @@ impl FromStr for HookType {
         use self::HookType::*;
 
         match s {
+            "BeforeAll" => Ok(BeforeAll),
+            "BeforeFeature" => Ok(BeforeFeature),
             "BeforeScenario" => Ok(BeforeScenario),
             "BeforeStep" => Ok(BeforeStep),
             "AfterStep" => Ok(AfterStep),
             "AfterScenario" => Ok(AfterScenario),
+            "AfterFeature" => Ok(AfterFeature),
+            "AfterAll" => Ok(AfterAll),
             _ => Err(()),
         }
     }
@@ -1,5 +1,3 @@
-use gherkin::cuke::Tag;
-
 #[derive(Debug, Clone)]
 pub enum Expression {
     Literal(LiteralExpression),
@@ -10,14 +8,14 @@ pub enum Expression {
 }
 
 impl Expression {
-    pub fn evaluate(&self, tags: &[Tag]) -> bool {
+    pub fn evaluate(&self, tag_names: &[&str]) -> bool {
         use self::Expression::*;
 
         match self {
-            Literal(expression) => expression.evaluate(tags),
-            Or(expression) => expression.evaluate(tags),
-            And(expression) => expression.evaluate(tags),
-            Not(expression) => expression.evaluate(tags),
+            Literal(expression) => expression.evaluate(tag_names),
+            Or(expression) => expression.evaluate(tag_names),
+            And(expression) => expression.evaluate(tag_names),
+            Not(expression) => expression.evaluate(tag_names),
             True => true,
         }
     }
@@ -29,8 +27,8 @@ pub struct LiteralExpression {
 }
 
 impl LiteralExpression {
-    pub fn evaluate(&self, tags: &[Tag]) -> bool {
-        tags.iter().any(|tag| tag.name == self.value)
+    pub fn evaluate(&self, tag_names: &[&str]) -> bool {
+        tag_names.iter().any(|tag_name| *tag_name == self.value)
     }
 }
 
@@ -41,8 +39,8 @@ pub struct OrExpression {
 }
 
 impl OrExpression {
-    pub fn evaluate(&self, tags: &[Tag]) -> bool {
-        self.left.evaluate(tags) || self.right.evaluate(tags)
+    pub fn evaluate(&self, tag_names: &[&str]) -> bool {
+        self.left.evaluate(tag_names) || self.right.evaluate(tag_names)
     }
 }
 
@@ -53,8 +51,8 @@ pub struct AndExpression {
 }
 
 impl AndExpression {
-    pub fn evaluate(&self, tags: &[Tag]) -> bool {
-        self.left.evaluate(tags) && self.right.evaluate(tags)
+    pub fn evaluate(&self, tag_names: &[&str]) -> bool {
+        self.left.evaluate(tag_names) && self.right.evaluate(tag_names)
     }
 }
 
@@ -64,7 +62,7 @@ pub struct NotExpression {
 }
 
 impl NotExpression {
-    pub fn evaluate(&self, tags: &[Tag]) -> bool {
-        !self.expression.evaluate(tags)
+    pub fn evaluate(&self, tag_names: &[&str]) -> bool {
+        !self.expression.evaluate(tag_names)
     }
 }
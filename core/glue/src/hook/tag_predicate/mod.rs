@@ -7,21 +7,34 @@ use self::expression::Expression;
 
 #[derive(Debug, Clone)]
 pub struct TagPredicate {
-    text: &'static str,
+    text: String,
     expression: Expression,
 }
 
 impl TagPredicate {
-    pub fn new(tag_expression: &'static str) -> Result<TagPredicate, String> {
+    pub fn new(tag_expression: &str) -> Result<TagPredicate, String> {
         let tag_predicate = TagPredicate {
-            text: tag_expression,
+            text: tag_expression.to_owned(),
             expression: parser::parse(tag_expression)?,
         };
         Ok(tag_predicate)
     }
 
+    /// The tag expression as written in the hook attribute.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
     pub fn test(&self, tags: &[Tag]) -> bool {
-        self.expression.evaluate(tags)
+        let tag_names = tags.iter()
+            .map(|tag| tag.name)
+            .collect::<Vec<&str>>();
+        self.test_names(&tag_names)
+    }
+
+    /// Tests the predicate against plain tag names, e.g. the tags of a feature.
+    pub fn test_names(&self, tag_names: &[&str]) -> bool {
+        self.expression.evaluate(tag_names)
     }
 }
 
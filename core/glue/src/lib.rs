@@ -8,6 +8,7 @@ pub mod error;
 pub mod scenario;
 pub mod step;
 pub mod hook;
+pub mod parameter_type;
 
 use std::fmt;
 
@@ -24,9 +25,14 @@ impl fmt::Display for CodeLocation {
 }
 
 pub struct StaticGlueDefinitions {
+    pub parameter_types: &'static [&'static parameter_type::StaticParameterTypeDef],
+    pub before_all_hooks: &'static [&'static hook::StaticHookDef],
+    pub before_feature_hooks: &'static [&'static hook::StaticHookDef],
     pub before_scenario_hooks: &'static [&'static hook::StaticHookDef],
     pub before_step_hooks: &'static [&'static hook::StaticHookDef],
     pub steps: &'static [&'static step::StaticStepDef],
     pub after_step_hooks: &'static [&'static hook::StaticHookDef],
     pub after_scenario_hooks: &'static [&'static hook::StaticHookDef],
+    pub after_feature_hooks: &'static [&'static hook::StaticHookDef],
+    pub after_all_hooks: &'static [&'static hook::StaticHookDef],
 }
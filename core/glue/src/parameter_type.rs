@@ -0,0 +1,23 @@
+use crate::CodeLocation;
+
+/// Generated info for a parameter type definition
+/// (a `#[parameter_type(...)]` annotated function).
+///
+/// A parameter type can be referenced in cucumber expressions by its name (like `{currency}`)
+/// and the matched text is converted to the return type of the annotated function.
+///
+/// The conversion is generated as a function named after the parameter type, which the
+/// step functions call for their `{name}` parameters. The annotated function therefore has
+/// to be in scope of the step functions that use it (for example by a glob import).
+#[derive(Debug)]
+pub struct StaticParameterTypeDef {
+    /// Name of the parameter type as used in cucumber expressions.
+    pub name: &'static str,
+    /// The regex that matches the text of the parameter.
+    ///
+    /// If the regex contains capture groups, the first matching group
+    /// is passed to the annotated function instead of the whole match.
+    pub regex: &'static str,
+    /// Location of the user defined annotated function.
+    pub location: CodeLocation,
+}
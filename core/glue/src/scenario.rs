@@ -1,19 +1,37 @@
 use std::fmt;
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[derive(Debug, Default)]
 pub struct Scenario {
     user_data: HashMap<TypeId, Box<Any>>,
+    feature_data: Arc<FeatureData>,
 }
 
 impl Scenario {
     pub fn new() -> Scenario {
         Scenario {
             user_data: HashMap::new(),
+            feature_data: Arc::new(FeatureData::default()),
         }
     }
 
+    /// Creates a scenario that shares the feature data of its feature (read-only).
+    #[doc(hidden)]
+    pub fn with_feature_data(feature_data: Arc<FeatureData>) -> Scenario {
+        Scenario {
+            user_data: HashMap::new(),
+            feature_data,
+        }
+    }
+
+    /// Returns a handle to the feature data, to share it with the scenarios of the feature.
+    #[doc(hidden)]
+    pub fn share_feature_data(&self) -> Arc<FeatureData> {
+        self.feature_data.clone()
+    }
+
     pub fn set_user_data<T: 'static>(&mut self, user_data: T) {
         self.user_data.insert(TypeId::of::<T>(), Box::new(user_data));
     }
@@ -22,6 +40,41 @@ impl Scenario {
         self.user_data.get_mut(&TypeId::of::<T>())
             .map(|value| value.downcast_mut::<T>().unwrap())
     }
+
+    /// Stores data that all scenarios of the current feature can read with `get_feature_data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the feature data is already shared with the scenarios of the feature,
+    /// so it must be set in a `BeforeFeature` hook.
+    pub fn set_feature_data<T: Send + Sync + 'static>(&mut self, feature_data: T) {
+        match Arc::get_mut(&mut self.feature_data) {
+            Some(data) => data.insert(feature_data),
+            None => panic!("feature data can only be set in `BeforeFeature` hooks"),
+        }
+    }
+
+    pub fn get_feature_data<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.feature_data.get()
+    }
+}
+
+/// Data of a feature file that is set by `BeforeFeature` hooks
+/// and shared (read-only) with all scenarios of the feature, even when they run in parallel.
+#[derive(Debug, Default)]
+pub struct FeatureData {
+    data: HashMap<TypeId, Box<Any + Send + Sync>>,
+}
+
+impl FeatureData {
+    fn insert<T: Send + Sync + 'static>(&mut self, data: T) {
+        self.data.insert(TypeId::of::<T>(), Box::new(data));
+    }
+
+    fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.data.get(&TypeId::of::<T>())
+            .map(|value| value.downcast_ref::<T>().unwrap())
+    }
 }
 
 pub type FromScenarioResult<T> = ::std::result::Result<T, FromScenarioError>;
@@ -63,3 +116,27 @@ impl<'a> FromScenario<'a> for &'a mut Scenario {
         Ok(scenario)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feature_data_is_shared_with_the_scenarios_of_the_feature() {
+        let mut feature_scenario = Scenario::new();
+        feature_scenario.set_feature_data(42u32);
+
+        let scenario = Scenario::with_feature_data(feature_scenario.share_feature_data());
+        assert_eq!(scenario.get_feature_data::<u32>(), Some(&42));
+        assert_eq!(scenario.get_feature_data::<i64>(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "feature data can only be set in `BeforeFeature` hooks")]
+    fn feature_data_cannot_be_set_once_it_is_shared() {
+        let feature_scenario = Scenario::new();
+        let mut scenario = Scenario::with_feature_data(feature_scenario.share_feature_data());
+
+        scenario.set_feature_data(42u32);
+    }
+}
@@ -0,0 +1,294 @@
+mod parser;
+
+use regex::{self, Regex};
+
+/// The built-in parameter types that can be used in every cucumber expression
+/// as `{name}`, together with the regex that matches them.
+pub const BUILTIN_PARAMETER_TYPES: &[(&str, &str)] = &[
+    ("int", r"-?\d+"),
+    ("float", r"[-+]?(?:\d+(?:\.\d*)?|\.\d+)(?:[eE][-+]?\d+)?"),
+    ("word", r"[^\s]+"),
+    ("string", r#""([^"\\]*(?:\\.[^"\\]*)*)"|'([^'\\]*(?:\\.[^'\\]*)*)'"#),
+    ("", r".*"),
+];
+
+/// Returns the regex of the built-in parameter type with the given name.
+pub fn builtin_parameter_type_regex(name: &str) -> Option<&'static str> {
+    BUILTIN_PARAMETER_TYPES.iter()
+        .find(|(builtin_name, _)| *builtin_name == name)
+        .map(|(_, regex)| *regex)
+}
+
+/// Returns whether the step expression should be treated as a regex
+/// instead of a cucumber expression.
+///
+/// Following cucumber conventions, this is the case when the expression
+/// is anchored with a leading `^` or a trailing `$`.
+///
+/// Step expressions without anchors are parsed as cucumber expressions, so a
+/// regex like `I add (\d+) and (\d+)` has to be anchored (`^I add (\d+) and (\d+)$`)
+/// or rewritten as a cucumber expression (`I add {int} and {int}`) to keep matching.
+pub fn is_regex(expression: &str) -> bool {
+    expression.starts_with('^') || expression.ends_with('$')
+}
+
+/// Returns whether an unanchored step expression contains a regex character class escape
+/// like `\d`, which hints at a regex that is missing its anchors.
+///
+/// Other regex characters like `+`, `[` or `|` are literal text in a cucumber expression
+/// (as in `I compute 1 + 2`), so they are no sign of a regex.
+pub fn contains_regex_syntax(expression: &str) -> bool {
+    let mut chars = expression.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('d') | Some('D') | Some('w') | Some('W') | Some('s') | Some('S') | Some('b') => return true,
+                _ => {},
+            }
+        }
+    }
+
+    false
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Node {
+    Text(String),
+    Optional(String),
+    Alternation(Vec<Vec<Node>>),
+    Parameter(String),
+}
+
+/// A parsed cucumber expression like `I have {int} cucumber(s) in my belly/stomach`.
+#[derive(Debug, Clone)]
+pub struct CucumberExpression {
+    nodes: Vec<Node>,
+}
+
+/// A cucumber expression compiled to a regex.
+#[derive(Debug, Clone)]
+pub struct CompiledExpression {
+    /// The anchored regex that matches the whole step text.
+    pub regex: String,
+    /// The capture groups of the parameters in the order of their appearance.
+    pub parameter_groups: Vec<ParameterGroup>,
+}
+
+/// The capture group of a single parameter in a `CompiledExpression` regex.
+///
+/// If the regex of the parameter type contains capture groups itself,
+/// the first matching inner group is the value of the parameter
+/// (this is how `{string}` strips its quotes).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParameterGroup {
+    /// Index of the capture group that encloses the whole parameter.
+    pub index: usize,
+    /// Number of capture groups within the parameter type regex.
+    pub inner_groups: usize,
+}
+
+impl CucumberExpression {
+    pub fn parse(expression: &str) -> Result<CucumberExpression, String> {
+        let cucumber_expression = CucumberExpression {
+            nodes: parser::parse(expression)?,
+        };
+        Ok(cucumber_expression)
+    }
+
+    /// Returns the names of all parameter types used in this expression.
+    pub fn parameter_type_names(&self) -> Vec<&str> {
+        self.nodes.iter()
+            .filter_map(|node| match node {
+                Node::Parameter(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Compiles the expression to a regex.
+    ///
+    /// The `parameter_type_regex` function resolves the regex of a parameter type by its name.
+    pub fn compile<F>(&self, parameter_type_regex: F) -> Result<CompiledExpression, String>
+        where F: Fn(&str) -> Option<String>
+    {
+        let mut regex = String::from("^");
+        let mut parameter_groups = Vec::new();
+        let mut next_group_index = 1;
+
+        for node in &self.nodes {
+            match node {
+                Node::Parameter(name) => {
+                    let parameter_regex = parameter_type_regex(name)
+                        .ok_or_else(|| format!("undefined parameter type {{{}}}", name))?;
+                    let inner_groups = Regex::new(&parameter_regex)
+                        .map(|compiled| compiled.captures_len() - 1)
+                        .map_err(|err| format!("parameter type {{{}}} has an invalid regex: {}", name, err))?;
+
+                    regex.push('(');
+                    regex.push_str(&parameter_regex);
+                    regex.push(')');
+
+                    parameter_groups.push(ParameterGroup {
+                        index: next_group_index,
+                        inner_groups,
+                    });
+                    next_group_index += 1 + inner_groups;
+                },
+                Node::Alternation(alternatives) => {
+                    let alternatives = alternatives.iter()
+                        .map(|alternative| alternative.iter().map(text_regex).collect::<String>())
+                        .collect::<Vec<String>>();
+
+                    regex.push_str("(?:");
+                    regex.push_str(&alternatives.join("|"));
+                    regex.push(')');
+                },
+                node => regex.push_str(&text_regex(node)),
+            }
+        }
+
+        regex.push('$');
+
+        Ok(CompiledExpression {
+            regex,
+            parameter_groups,
+        })
+    }
+}
+
+fn text_regex(node: &Node) -> String {
+    match node {
+        Node::Text(text) => regex::escape(text),
+        Node::Optional(optional) => format!("(?:{})?", regex::escape(optional)),
+        Node::Alternation(_) | Node::Parameter(_) => unreachable!("only text is allowed in alternatives"),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(expression: &str) -> Result<CompiledExpression, String> {
+        CucumberExpression::parse(expression)?
+            .compile(|name| builtin_parameter_type_regex(name).map(str::to_owned))
+    }
+
+    fn match_arguments(expression: &str, text: &str) -> Option<Vec<String>> {
+        let compiled = compile(expression).unwrap();
+        let regex = Regex::new(&compiled.regex).unwrap();
+        let captures = regex.captures(text)?;
+
+        let arguments = compiled.parameter_groups.iter()
+            .map(|group| {
+                (group.index + 1..=group.index + group.inner_groups)
+                    .filter_map(|index| captures.get(index))
+                    .next()
+                    .or_else(|| captures.get(group.index))
+                    .map(|mat| mat.as_str().to_owned())
+                    .unwrap()
+            })
+            .collect();
+        Some(arguments)
+    }
+
+    #[test]
+    fn regex_syntax_is_detected() {
+        assert!(contains_regex_syntax(r"I add (\d+) and (\d+)"));
+        assert!(contains_regex_syntax(r"I am \w+"));
+        assert!(!contains_regex_syntax("I add {int} and {int}"));
+        assert!(!contains_regex_syntax("I compute 1 + 2"));
+        assert!(!contains_regex_syntax("I have 5+ items in [my] basket|cart"));
+        assert!(!contains_regex_syntax(r"I escape \\d"));
+        assert!(!contains_regex_syntax("I have {int} cucumber(s) in my belly/stomach"));
+    }
+
+    #[test]
+    fn text_only_expression_matches_whole_text() {
+        assert_eq!(match_arguments("a calculator", "a calculator"), Some(vec![]));
+        assert_eq!(match_arguments("a calculator", "a calculator I just turned on"), None);
+    }
+
+    #[test]
+    fn int_parameters_are_matched() {
+        assert_eq!(match_arguments("I add {int} and {int}", "I add 4 and -5"),
+            Some(vec!["4".to_owned(), "-5".to_owned()]));
+        assert_eq!(match_arguments("I add {int} and {int}", "I add 4 and five"), None);
+    }
+
+    #[test]
+    fn float_parameter_is_matched() {
+        assert_eq!(match_arguments("the result is {float}", "the result is 3.14"),
+            Some(vec!["3.14".to_owned()]));
+        assert_eq!(match_arguments("the result is {float}", "the result is 9"),
+            Some(vec!["9".to_owned()]));
+    }
+
+    #[test]
+    fn word_and_anonymous_parameters_are_matched() {
+        assert_eq!(match_arguments("I press {word}", "I press +"), Some(vec!["+".to_owned()]));
+        assert_eq!(match_arguments("I press {word}", "I press + twice"), None);
+        assert_eq!(match_arguments("I say {}", "I say hello world"),
+            Some(vec!["hello world".to_owned()]));
+    }
+
+    #[test]
+    fn string_parameter_strips_quotes() {
+        assert_eq!(match_arguments("I type {string}", "I type \"hello world\""),
+            Some(vec!["hello world".to_owned()]));
+        assert_eq!(match_arguments("I type {string} and {int}", "I type 'hi' and 3"),
+            Some(vec!["hi".to_owned(), "3".to_owned()]));
+    }
+
+    #[test]
+    fn optional_text_is_matched() {
+        assert_eq!(match_arguments("I have {int} cucumber(s)", "I have 1 cucumber"),
+            Some(vec!["1".to_owned()]));
+        assert_eq!(match_arguments("I have {int} cucumber(s)", "I have 2 cucumbers"),
+            Some(vec!["2".to_owned()]));
+    }
+
+    #[test]
+    fn alternative_text_is_matched() {
+        let expression = "I have {int} cucumber(s) in my belly/stomach";
+        assert!(match_arguments(expression, "I have 2 cucumbers in my belly").is_some());
+        assert!(match_arguments(expression, "I have 2 cucumbers in my stomach").is_some());
+        assert!(match_arguments(expression, "I have 2 cucumbers in my belly/stomach").is_none());
+    }
+
+    #[test]
+    fn escaped_characters_are_matched_literally() {
+        assert_eq!(match_arguments("I use \\{int\\} \\(literally\\) a\\/b", "I use {int} (literally) a/b"),
+            Some(vec![]));
+        assert_eq!(match_arguments("it costs $1.00", "it costs $1.00"), Some(vec![]));
+        assert_eq!(match_arguments("it costs $1.00", "it costs $1x00"), None);
+        assert_eq!(match_arguments("I compute {int} + {int} [exactly]", "I compute 1 + 2 [exactly]"),
+            Some(vec!["1".to_owned(), "2".to_owned()]));
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        assert!(compile("I add {int and {int}").is_err());
+        assert!(compile("I add (int").is_err());
+        assert!(compile("I add int)").is_err());
+        assert!(compile("I have () cucumbers").is_err());
+        assert!(compile("I have {int} cucumbers/{int}").is_err());
+        assert!(compile("I have a/ cucumber").is_err());
+        assert!(compile("I have a ({int}) cucumber").is_err());
+        assert!(compile("trailing escape \\").is_err());
+    }
+
+    #[test]
+    fn undefined_parameter_type_is_rejected() {
+        assert_eq!(compile("I pay {currency}").unwrap_err(), "undefined parameter type {currency}");
+    }
+
+    #[test]
+    fn regexes_are_detected_by_anchors() {
+        assert!(is_regex("^I add (\\d+) and (\\d+)$"));
+        assert!(is_regex("^I add"));
+        assert!(is_regex("I add$"));
+        assert!(!is_regex("I add {int} and {int}"));
+    }
+}
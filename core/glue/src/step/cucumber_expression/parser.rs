@@ -0,0 +1,158 @@
+use super::Node;
+
+const ESCAPING_CHAR: char = '\\';
+
+pub fn parse(expression: &str) -> Result<Vec<Node>, String> {
+    let tokens = tokenize(expression)?;
+
+    let mut nodes = Vec::new();
+    let mut word = Vec::new();
+
+    for token in tokens {
+        match token {
+            Token::Whitespace(whitespace) => {
+                push_word(&mut word, &mut nodes)?;
+                nodes.push(Node::Text(whitespace));
+            },
+            token => word.push(token),
+        }
+    }
+    push_word(&mut word, &mut nodes)?;
+
+    Ok(nodes)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum Token {
+    Text(String),
+    Whitespace(String),
+    Optional(String),
+    Parameter(String),
+    Alternation,
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut chars = expression.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ESCAPING_CHAR => {
+                match chars.next() {
+                    Some(escaped) => text.push(escaped),
+                    None => return Err(String::from("expression must not end with an escape character")),
+                }
+            },
+            '(' => {
+                flush_text(&mut text, &mut tokens);
+                let optional = read_until(&mut chars, ')', "missing closing parenthesis ')'",
+                    &['(', '{'], "optional text may not contain parameter types or nested optional text")?;
+                if optional.is_empty() {
+                    return Err(String::from("optional text must not be empty"));
+                }
+                tokens.push(Token::Optional(optional));
+            },
+            '{' => {
+                flush_text(&mut text, &mut tokens);
+                let name = read_until(&mut chars, '}', "missing closing brace '}'",
+                    &['{', '(', '/'], "parameter type names may not contain '{', '(' or '/'")?;
+                if name.chars().any(char::is_whitespace) {
+                    return Err(format!("parameter type name \"{}\" must not contain whitespace", name));
+                }
+                tokens.push(Token::Parameter(name));
+            },
+            ')' => return Err(String::from("missing opening parenthesis '('")),
+            '}' => return Err(String::from("missing opening brace '{'")),
+            '/' => {
+                flush_text(&mut text, &mut tokens);
+                tokens.push(Token::Alternation);
+            },
+            c if c.is_whitespace() => {
+                flush_text(&mut text, &mut tokens);
+                match tokens.last_mut() {
+                    Some(Token::Whitespace(whitespace)) => whitespace.push(c),
+                    _ => tokens.push(Token::Whitespace(c.to_string())),
+                }
+            },
+            c => text.push(c),
+        }
+    }
+    flush_text(&mut text, &mut tokens);
+
+    Ok(tokens)
+}
+
+fn read_until<I: Iterator<Item=char>>(chars: &mut I, end: char, missing_end_message: &str,
+    forbidden: &[char], forbidden_message: &str) -> Result<String, String> {
+
+    let mut value = String::new();
+
+    while let Some(c) = chars.next() {
+        if c == ESCAPING_CHAR {
+            match chars.next() {
+                Some(escaped) => value.push(escaped),
+                None => return Err(String::from("expression must not end with an escape character")),
+            }
+        } else if c == end {
+            return Ok(value);
+        } else if forbidden.contains(&c) {
+            return Err(String::from(forbidden_message));
+        } else {
+            value.push(c);
+        }
+    }
+
+    Err(String::from(missing_end_message))
+}
+
+fn flush_text(text: &mut String, tokens: &mut Vec<Token>) {
+    if !text.is_empty() {
+        tokens.push(Token::Text(text.split_off(0)));
+    }
+}
+
+/// Converts the tokens of a single word (a sequence without whitespace) into nodes.
+///
+/// Alternations are only valid within a word, so this is where they are resolved.
+fn push_word(word: &mut Vec<Token>, nodes: &mut Vec<Node>) -> Result<(), String> {
+    let tokens = word.split_off(0);
+
+    if !tokens.contains(&Token::Alternation) {
+        nodes.extend(tokens.into_iter().map(into_node));
+        return Ok(());
+    }
+
+    let mut alternatives = Vec::new();
+    let mut alternative = Vec::new();
+
+    for token in tokens.into_iter().chain(Some(Token::Alternation)) {
+        match token {
+            Token::Alternation => {
+                if alternative.is_empty() {
+                    return Err(String::from("alternative text must not be empty"));
+                }
+                if alternative.iter().all(|node| match node { Node::Optional(_) => true, _ => false }) {
+                    return Err(String::from("alternative text must not consist of optional text only"));
+                }
+                alternatives.push(alternative.split_off(0));
+            },
+            Token::Parameter(name) => {
+                return Err(format!("parameter type {{{}}} must not be used in alternative text", name));
+            },
+            token => alternative.push(into_node(token)),
+        }
+    }
+
+    nodes.push(Node::Alternation(alternatives));
+    Ok(())
+}
+
+fn into_node(token: Token) -> Node {
+    match token {
+        Token::Text(text) | Token::Whitespace(text) => Node::Text(text),
+        Token::Optional(optional) => Node::Optional(optional),
+        Token::Parameter(name) => Node::Parameter(name),
+        Token::Alternation => unreachable!("alternations are resolved per word"),
+    }
+}
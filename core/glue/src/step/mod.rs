@@ -1,4 +1,6 @@
 pub mod argument;
+#[doc(hidden)]
+pub mod cucumber_expression;
 
 use std::fmt;
 use std::str::FromStr;
@@ -18,6 +20,9 @@ pub struct StaticStepDef {
     /// Step definition keyword like "Given", "When" and "Then".
     pub keyword: StepKeyword,
     /// The step definition expression to match a step in a cucumber scenario.
+    ///
+    /// This is either a cucumber expression like `I add {int} and {int}`
+    /// or a regex that is anchored with `^` or `$` like `^I add (\\d+) and (\\d+)$`.
     pub expression: &'static str,
     /// The generated step handler function that will call the user defined annotated function.
     pub step_fn: StepFn,
@@ -4,14 +4,24 @@ use std::time::SystemTime;
 use gherkin::ast::{Feature, Background};
 use gherkin::cuke::{Cuke, ScenarioDefinition};
 
-use api::{TestCase, TestStep, TestResult};
+use api::{TestCase, TestStep, TestResult, StepDefinitionInfo, HookDefinitionInfo};
 
 /// An execution event that is generated by cucumber.
 #[derive(Debug)]
 pub enum Event<'e, 's: 'e> {
+    /// Sent once with the step definitions and hooks of the glue code.
+    ///
+    /// This is the first event that will be sent.
+    GlueLoaded {
+        time: SystemTime,
+        /// Sorted by their code location.
+        step_definitions: &'e [StepDefinitionInfo<'e>],
+        /// In execution order, grouped by hook type.
+        hook_definitions: &'e [HookDefinitionInfo<'e>],
+    },
     /// Sent for each feature file that was successfully parsed.
     ///
-    /// These events are currently first ones being sent, but this may be changed in the future.
+    /// These events are sent after the `GlueLoaded` event and before any test case is started.
     TestSourceRead {
         time: SystemTime,
         uri: &'e str,
@@ -19,11 +29,33 @@ pub enum Event<'e, 's: 'e> {
         feature: &'e Feature,
         cukes: &'e [Cuke<'e>],
     },
+    /// Sent after the execution of a global hook (`BeforeAll` or `AfterAll`).
+    ///
+    /// `BeforeAll` hooks are executed before the `TestRunStarted` event
+    /// and `AfterAll` hooks after the last test case.
+    GlobalHookFinished {
+        time: SystemTime,
+        test_step: &'e TestStep<'s>,
+        result: &'e TestResult,
+    },
+    /// Sent after the execution of a feature hook (`BeforeFeature` or `AfterFeature`).
+    ///
+    /// `BeforeFeature` hooks are executed before the first test case of a feature file
+    /// and `AfterFeature` hooks after its last test case.
+    FeatureHookFinished {
+        time: SystemTime,
+        uri: &'e str,
+        feature: &'e Feature,
+        test_step: &'e TestStep<'s>,
+        result: &'e TestResult,
+    },
     /// Sent before any execution of test steps is started,
     /// but after all feature files have been parsed.
     TestRunStarted {
         time: SystemTime,
         num_cukes: usize,
+        /// The number of cukes that are not run because of the tag, location or name filters.
+        num_filtered_out: usize,
     },
     /// Sent before starting the execution of a test case.
     TestCaseStarted {
@@ -33,6 +65,8 @@ pub enum Event<'e, 's: 'e> {
         background: Option<&'e Background>,
         scenario_definition: &'e ScenarioDefinition<'e>,
         test_case: &'e TestCase,
+        /// The attempt of running the test case, starting at 0 (failed test cases can be retried).
+        attempt: u32,
     },
     /// Sent after the execution of a test case.
     TestStepStarted {
@@ -47,12 +81,20 @@ pub enum Event<'e, 's: 'e> {
     /// Sent when a hook wants to embed media into a report.
     Embed {
         time: SystemTime,
+        /// The feature file of the running test case.
+        uri: &'e str,
+        /// The line of the running test case (see `TestCase::get_line`).
+        line: u32,
         data: &'e [u8],
         mime_type: String,
     },
     /// Sent when a hook wants to add some text to a report.
     Write {
         time: SystemTime,
+        /// The feature file of the running test case.
+        uri: &'e str,
+        /// The line of the running test case (see `TestCase::get_line`).
+        line: u32,
         text: &'e str,
     },
     /// Sent after the execution of a test step.
@@ -66,6 +108,14 @@ pub enum Event<'e, 's: 'e> {
         test_step: &'e TestStep<'s>,
         result: &'e TestResult,
     },
+    /// Sent when a step without a matching step definition was executed,
+    /// suggesting step definition snippets that would implement the step.
+    SnippetsSuggested {
+        time: SystemTime,
+        uri: &'e str,
+        step_line: u32,
+        snippets: &'e [String],
+    },
     /// Sent after the execution of a test step.
     TestCaseFinished {
         time: SystemTime,
@@ -75,6 +125,9 @@ pub enum Event<'e, 's: 'e> {
         scenario_definition: &'e ScenarioDefinition<'e>,
         test_case: &'e TestCase,
         result: &'e TestResult,
+        attempt: u32,
+        /// Whether the test case failed and is run again, so this is not its final result.
+        will_be_retried: bool,
     },
     /// Sent after all executions of test steps are finished.
     ///
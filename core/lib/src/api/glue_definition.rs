@@ -0,0 +1,20 @@
+use api::{CodeLocation, HookType};
+
+/// A step definition of the glue code (a `#[step(...)]` annotated function).
+#[derive(Debug, Copy, Clone)]
+pub struct StepDefinitionInfo<'g> {
+    /// The cucumber expression or regex as written in the step attribute.
+    pub expression: &'g str,
+    /// Whether the expression is a regex (anchored with `^` or `$`) instead of a cucumber expression.
+    pub is_regex: bool,
+    pub location: &'g CodeLocation,
+}
+
+/// A hook definition of the glue code (for example a `#[before_scenario(...)]` annotated function).
+#[derive(Debug, Copy, Clone)]
+pub struct HookDefinitionInfo<'g> {
+    pub hook_type: HookType,
+    /// The tag expression restricting when the hook is executed, empty if it is always executed.
+    pub tag_expression: &'g str,
+    pub location: &'g CodeLocation,
+}
@@ -1,10 +1,12 @@
 pub use self::feature_file::FeatureFile;
+pub use self::glue_definition::{StepDefinitionInfo, HookDefinitionInfo};
 pub use self::test_step::{TestStep, HookTestStep, HookType, CukeStepTestStep};
 pub use self::test_case::TestCase;
 pub use self::test_result::{TestResult, TestResultStatus};
 pub use glue::CodeLocation;
 
 mod feature_file;
+mod glue_definition;
 mod test_step;
 mod test_case;
 mod test_result;
@@ -15,19 +15,30 @@ pub enum TestStep<'s> {
 
 #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
 pub enum HookType {
+    BeforeAll,
+    BeforeFeature,
     BeforeScenario,
     AfterScenario,
     BeforeStep,
     AfterStep,
+    AfterFeature,
+    AfterAll,
 }
 
 /// Hooks are invoked before and after each scenario and before and after each gherkin step in a scenario.
+///
+/// Global hooks (`BeforeAll` and `AfterAll`) are invoked once before and after all scenarios
+/// and feature hooks (`BeforeFeature` and `AfterFeature`) once before and after all scenarios
+/// of a feature file.
 pub trait HookTestStep<'s>: Debug + Send + Sync {
     /// Representation of the source code location of the glue.
     fn get_code_location(&self) -> Option<&CodeLocation>;
 
     /// The hook hook type (BeforeScenario, AfterScenario, ...).
     fn get_hook_type(&self) -> HookType;
+
+    /// The execution order among the hooks of the same hook type.
+    fn get_order(&self) -> isize;
 }
 
 /// A cuke test step matches a line in a Gherkin scenario or background.
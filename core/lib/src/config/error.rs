@@ -1,6 +1,9 @@
 use std::fmt;
 use std::io;
 use std::env;
+use std::path::PathBuf;
+
+use toml;
 
 /// The specific type of an error.
 #[derive(Fail, Debug)]
@@ -11,6 +14,17 @@ pub enum Error {
     EnvVar(#[cause] env::VarError, &'static str),
     /// The configuration file was not found.
     NotFound,
+    /// The configuration file at the path is not valid.
+    Toml(#[cause] toml::de::Error, PathBuf),
+    /// A setting (from an environment variable or argument) has an invalid value.
+    InvalidValue {
+        /// The environment variable or argument name of the setting.
+        setting: String,
+        value: String,
+        message: String,
+    },
+    /// The settings cannot be combined with each other.
+    Conflict(String),
     /// Hints that destructuring should not be exhaustive.
     ///
     /// This enum may grow additional variants, so this makes sure clients
@@ -33,6 +47,13 @@ impl fmt::Display for Error {
             Error::EnvVar(ref err, ref var) =>
                 write!(f, "required env variable \"{}\" could not be used: {}", var, err),
             Error::NotFound => write!(f, "config file was not found"),
+            Error::Toml(ref err, ref path) => match err.line_col() {
+                Some((line, column)) => write!(f, "{}:{}:{}: {}", path.display(), line + 1, column + 1, err),
+                None => write!(f, "{}: {}", path.display(), err),
+            },
+            Error::InvalidValue { ref setting, ref value, ref message } =>
+                write!(f, "invalid value \"{}\" for {}: {}", value, setting, message),
+            Error::Conflict(ref message) => write!(f, "conflicting settings: {}", message),
             Error::__Nonexhaustive => unreachable!(),
         }
     }
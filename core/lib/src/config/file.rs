@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use toml;
+
+use super::{Error, ExecutionModeKind, Formatter, Order};
+
+const CONFIG_FILE_NAME: &str = "cukes.toml";
+const PROFILE_ENV_VAR: &str = "CUKE_PROFILE";
+
+/// The settings of the `cukes.toml` config file at the crate root.
+///
+/// Named profiles (like `[profile.ci]`) override the top level settings
+/// if they are selected with the `CUKE_PROFILE` environment variable:
+///
+/// ```toml
+/// features = ["tests/features", "../shared/features/*.feature"]
+/// exclude_features = ["**/wip/*.feature"]
+/// strict = true
+/// formatters = ["pretty"]
+/// execution_mode = "sequential"
+///
+/// [profile.ci]
+/// formatters = ["progress"]
+/// execution_mode = "parallel-scenarios"
+///
+/// [profile.smoke]
+/// tags = ["@smoke"]
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Relative to the crate root.
+    pub features: Option<Vec<PathBuf>>,
+    pub exclude_features: Option<Vec<String>>,
+    /// Relative to the crate root.
+    pub output_dir: Option<PathBuf>,
+    pub strict: Option<bool>,
+    pub fail_fast: Option<bool>,
+    pub retry: Option<u32>,
+    pub order: Option<Order>,
+    /// Relative to the crate root.
+    pub rerun_file: Option<PathBuf>,
+    pub tags: Option<Vec<String>>,
+    pub formatters: Option<Vec<Formatter>>,
+    pub execution_mode: Option<ExecutionModeKind>,
+    #[serde(default)]
+    profile: HashMap<String, ConfigFile>,
+}
+
+impl ConfigFile {
+    /// Reads the config file of the crate root with the profile of the `CUKE_PROFILE`
+    /// environment variable.
+    ///
+    /// Returns `None` if there is no config file and no profile is selected.
+    /// Without the `CARGO_MANIFEST_DIR` environment variable (if the test binary is not run
+    /// by cargo), the crate root is unknown, so there is no config file either.
+    pub fn load() -> Result<Option<ConfigFile>, Error> {
+        let crate_root = match env::var("CARGO_MANIFEST_DIR") {
+            Ok(crate_root) => Some(PathBuf::from(crate_root)),
+            Err(env::VarError::NotPresent) => None,
+            Err(err) => return Err(Error::EnvVar(err, "CARGO_MANIFEST_DIR")),
+        };
+        let profile = match env::var(PROFILE_ENV_VAR) {
+            Ok(profile) => Some(profile),
+            Err(env::VarError::NotPresent) => None,
+            Err(err) => return Err(Error::EnvVar(err, PROFILE_ENV_VAR)),
+        };
+
+        let crate_root = match crate_root {
+            Some(crate_root) if crate_root.join(CONFIG_FILE_NAME).is_file() => crate_root,
+            _ => return match profile {
+                Some(_) => Err(Error::NotFound),
+                None => Ok(None),
+            },
+        };
+        let path = crate_root.join(CONFIG_FILE_NAME);
+
+        let source = fs::read_to_string(&path)?;
+        let config_file = ConfigFile::parse(&path, &source)?;
+        let config_file = match profile {
+            Some(profile) => config_file.with_profile(&profile)?,
+            None => config_file,
+        };
+
+        Ok(Some(config_file.relative_to(&crate_root)))
+    }
+
+    fn parse(path: &Path, source: &str) -> Result<ConfigFile, Error> {
+        toml::from_str(source).map_err(|err| Error::Toml(err, path.to_path_buf()))
+    }
+
+    /// Overrides the top level settings with the settings of the profile.
+    fn with_profile(mut self, name: &str) -> Result<ConfigFile, Error> {
+        let profile = match self.profile.remove(name) {
+            Some(profile) => profile,
+            None => return Err(Error::InvalidValue {
+                setting: PROFILE_ENV_VAR.to_owned(),
+                value: name.to_owned(),
+                message: format!("the profile is not defined in {}", CONFIG_FILE_NAME),
+            }),
+        };
+
+        Ok(ConfigFile {
+            features: profile.features.or(self.features),
+            exclude_features: profile.exclude_features.or(self.exclude_features),
+            output_dir: profile.output_dir.or(self.output_dir),
+            strict: profile.strict.or(self.strict),
+            fail_fast: profile.fail_fast.or(self.fail_fast),
+            retry: profile.retry.or(self.retry),
+            order: profile.order.or(self.order),
+            rerun_file: profile.rerun_file.or(self.rerun_file),
+            tags: profile.tags.or(self.tags),
+            formatters: profile.formatters.or(self.formatters),
+            execution_mode: profile.execution_mode.or(self.execution_mode),
+            profile: HashMap::new(),
+        })
+    }
+
+    fn relative_to(mut self, crate_root: &Path) -> ConfigFile {
+        self.features = self.features.map(|features| features.iter()
+            .map(|feature| crate_root.join(feature))
+            .collect());
+        self.output_dir = self.output_dir.map(|output_dir| crate_root.join(output_dir));
+        self.rerun_file = self.rerun_file.map(|rerun_file| crate_root.join(rerun_file));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG_FILE: &str = r#"
+features = ["tests/features"]
+strict = true
+formatters = ["pretty"]
+
+[profile.ci]
+formatters = ["progress"]
+execution_mode = "parallel-scenarios"
+
+[profile.smoke]
+tags = ["@smoke"]
+"#;
+
+    #[test]
+    fn profile_overrides_top_level_settings() {
+        let config_file = ConfigFile::parse(Path::new(CONFIG_FILE_NAME), CONFIG_FILE).unwrap()
+            .with_profile("ci").unwrap();
+
+        assert_eq!(config_file, ConfigFile {
+            features: Some(vec![PathBuf::from("tests/features")]),
+            strict: Some(true),
+            formatters: Some(vec![Formatter::ProgressBar]),
+            execution_mode: Some(ExecutionModeKind::ParallelScenarios),
+            ..ConfigFile::default()
+        });
+    }
+
+    #[test]
+    fn undefined_profile_is_rejected() {
+        let config_file = ConfigFile::parse(Path::new(CONFIG_FILE_NAME), CONFIG_FILE).unwrap();
+
+        assert!(config_file.with_profile("nightly").is_err());
+    }
+
+    #[test]
+    fn invalid_setting_is_reported_with_its_line() {
+        let source = "strict = true\ntags = [\"@smoke\", 42]\n";
+
+        match ConfigFile::parse(Path::new(CONFIG_FILE_NAME), source) {
+            Err(Error::Toml(err, _)) => assert_eq!(err.line_col().map(|(line, _)| line), Some(1)),
+            result => panic!("expected a toml error, but got: {:?}", result),
+        }
+    }
+}
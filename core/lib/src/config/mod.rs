@@ -1,19 +1,50 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::default::Default;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
+use serde::de;
 
 pub use self::error::Error;
+pub use self::file::ConfigFile;
+pub use self::overrides::{ConfigOverrides, ExecutionModeKind, Formatter};
 use api::event::{EventListener, SyncEventListener};
 
 mod error;
+mod file;
+mod overrides;
+
+const RANDOM_ORDER_PREFIX: &str = "random:";
 
 #[derive(Debug)]
 pub struct Config<'c> {
-    pub features_dir: &'c Path,
+    /// Directories (searched recursively), feature files and glob patterns
+    /// (like `tests/**/smoke_*.feature`) of the feature files to run.
+    pub features: Vec<PathBuf>,
+    /// Glob patterns of feature files that are not run, even if they are selected by `features`.
+    ///
+    /// The patterns are matched against the whole path, so relative patterns should start with `**/`.
+    pub exclude_features: Vec<String>,
     pub output_dir: &'c Path,
     pub strict: bool,
     pub colored_output: bool,
     pub dry_run: bool,
+    /// Stops the run after the first test case that is not ok (like `Failed` or `Ambiguous`):
+    /// the remaining test cases are reported as skipped.
+    pub fail_fast: bool,
+    /// How many times a failed test case is run again, unless its cuke has a `@retry(n)` tag.
+    ///
+    /// Test cases that only pass after a retry are reported as flaky
+    /// (which fails the run in strict mode).
+    pub retry: u32,
     pub tags: Vec<String>,
+    pub feature_locations: Vec<String>,
+    pub names: Vec<String>,
+    /// A rerun file (written by the `rerun` formatter) whose feature locations are the only
+    /// selection of the cukes to run, replacing `features`, `exclude_features`,
+    /// `feature_locations`, `tags` and `names`.
+    pub rerun_file: Option<&'c Path>,
+    pub order: Order,
     pub execution_mode: ExecutionMode<'c>,
 }
 
@@ -34,6 +65,56 @@ pub enum ExecutionMode<'c> {
     },
 }
 
+/// The order in which the features, and the cukes of each feature, are run.
+///
+/// The cukes of a feature are always run together, so its feature hooks only run once.
+/// In the parallel execution modes, this is the order in which they are scheduled.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Order {
+    /// The order of the feature files and of the cukes in the feature files (`defined`).
+    Defined,
+    /// Sorted by the path of the feature files and by the lines of the cukes (`lexical`).
+    Lexical,
+    /// The reverse of the defined order (`reverse`).
+    Reverse,
+    /// Shuffled with a seed (`random` or `random:<seed>`), so the order can be reproduced.
+    ///
+    /// Without a seed, a new one is generated and printed at the start and end of the run.
+    Random {
+        seed: Option<u64>,
+    },
+}
+
+impl Default for Order {
+    fn default() -> Order {
+        Order::Defined
+    }
+}
+
+impl FromStr for Order {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Order, String> {
+        match s {
+            "defined" => Ok(Order::Defined),
+            "lexical" => Ok(Order::Lexical),
+            "reverse" => Ok(Order::Reverse),
+            "random" => Ok(Order::Random { seed: None }),
+            _ if s.starts_with(RANDOM_ORDER_PREFIX) => s[RANDOM_ORDER_PREFIX.len()..].parse()
+                .map(|seed| Order::Random { seed: Some(seed) })
+                .map_err(|_| "expected a number as seed of random:<seed>".to_owned()),
+            _ => Err("expected one of: defined, lexical, reverse, random, random:<seed>".to_owned()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Order {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Order, D::Error> {
+        let order = String::deserialize(deserializer)?;
+        order.parse().map_err(de::Error::custom)
+    }
+}
+
 impl<'c> Default for ExecutionMode<'c> {
     fn default() -> ExecutionMode<'c> {
         ExecutionMode::ParallelScenarios {
@@ -0,0 +1,536 @@
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use super::{Config, ConfigFile, Error, ExecutionMode, Order};
+
+const TAGS_ENV_VAR: &str = "CUKE_TAGS";
+const NAME_ENV_VAR: &str = "CUKE_NAME";
+const DRY_RUN_ENV_VAR: &str = "CUKE_DRY_RUN";
+const STRICT_ENV_VAR: &str = "CUKE_STRICT";
+const FAIL_FAST_ENV_VAR: &str = "CUKE_FAIL_FAST";
+const RETRY_ENV_VAR: &str = "CUKE_RETRY";
+const ORDER_ENV_VAR: &str = "CUKE_ORDER";
+const RERUN_FILE_ENV_VAR: &str = "CUKE_RERUN_FILE";
+const FORMAT_ENV_VAR: &str = "CUKE_FORMAT";
+const EXECUTION_MODE_ENV_VAR: &str = "CUKE_EXECUTION_MODE";
+const THREADS_ENV_VAR: &str = "CUKE_THREADS";
+
+/// The options of the default test harness, which are ignored (like their values, or any
+/// other argument that is not an option), so `cargo test -- --nocapture` still runs the cukes.
+const TEST_HARNESS_OPTIONS: &[&str] = &[
+    "--bench", "--color", "--ensure-time", "--exact", "--exclude-should-panic", "--force-run-in-process",
+    "--format", "--help", "--ignored", "--include-ignored", "--list", "--logfile", "--nocapture",
+    "--quiet", "--report-time", "--show-output", "--shuffle", "--shuffle-seed", "--skip", "--test",
+    "--test-threads",
+];
+
+/// Settings that override the `Config` of the test file without editing it.
+///
+/// They are read from the `cukes.toml` config file (see `ConfigFile`), `CUKE_*` environment variables
+/// and from the arguments after `cargo test -- ...`, where arguments take precedence
+/// over environment variables and environment variables over the config file:
+///
+/// ```text
+/// | Environment variable | Argument                    | Example value                        |
+/// |----------------------|-----------------------------|--------------------------------------|
+/// | CUKE_TAGS            | --tags (repeatable)         | @wip and not @slow                   |
+/// | CUKE_NAME            | --name (repeatable)         | ^Add two numbers$                    |
+/// | CUKE_DRY_RUN         | --dry-run                   | true                                 |
+/// | CUKE_STRICT          | --strict, --no-strict       | false                                |
+/// | CUKE_FAIL_FAST       | --fail-fast                 | true                                 |
+/// | CUKE_RETRY           | --retry                     | 2                                    |
+/// | CUKE_ORDER           | --order                     | random:42                            |
+/// | CUKE_RERUN_FILE      | --rerun-file                | target/cukes/rerun.txt               |
+/// | CUKE_FORMAT          | --cuke-format (repeatable)  | pretty,progress,json                 |
+/// | CUKE_EXECUTION_MODE  | --execution-mode            | sequential, parallel-features, ...   |
+/// | CUKE_THREADS         | --threads                   | 4                                    |
+/// ```
+///
+/// Values can also be given as `--retry=2`. The options of the default test harness
+/// (like `--nocapture`) and arguments that are not options are ignored,
+/// while other options are rejected, so a typo like `--tag` does not run all cukes.
+///
+/// The default test harness rejects these arguments, so they only reach the cukes if the
+/// test target disables it in `Cargo.toml`, and runs the cukes from its `main` function:
+///
+/// ```toml
+/// [[test]]
+/// name = "cukes"
+/// harness = false
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ConfigOverrides {
+    pub features: Option<Vec<PathBuf>>,
+    pub exclude_features: Option<Vec<String>>,
+    pub output_dir: Option<PathBuf>,
+    pub tags: Option<Vec<String>>,
+    pub names: Option<Vec<String>>,
+    pub dry_run: Option<bool>,
+    pub strict: Option<bool>,
+    pub fail_fast: Option<bool>,
+    pub retry: Option<u32>,
+    pub order: Option<Order>,
+    pub rerun_file: Option<PathBuf>,
+    /// Replaces the event listeners of the execution mode with the built-in formatters.
+    pub formatters: Option<Vec<Formatter>>,
+    pub execution_mode: Option<ExecutionModeKind>,
+    /// The number of threads for the parallel execution modes.
+    pub num_threads: Option<usize>,
+}
+
+/// A built-in formatter that can be selected by name.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+pub enum Formatter {
+    /// The `PrettyFormatter` (`pretty`), only supported by the sequential execution mode.
+    #[serde(rename = "pretty")]
+    Pretty,
+    /// The `ProgressBarListener` (`progress`).
+    #[serde(rename = "progress")]
+    ProgressBar,
+    /// The `RerunFormatter` (`rerun`), which writes `rerun.txt` to the output directory.
+    #[serde(rename = "rerun")]
+    Rerun,
+    /// The `JsonFormatter` (`json`), which writes `cucumber.json` to the output directory.
+    #[serde(rename = "json")]
+    Json,
+    /// The `JUnitFormatter` (`junit`), which writes `junit.xml` to the output directory.
+    #[serde(rename = "junit")]
+    JUnit,
+    /// The `NdjsonFormatter` (`ndjson`), which writes the Cucumber Messages protocol
+    /// to `cucumber.ndjson` in the output directory.
+    #[serde(rename = "ndjson")]
+    Ndjson,
+    /// The `HtmlFormatter` (`html`), which writes `cucumber.html` to the output directory.
+    #[serde(rename = "html")]
+    Html,
+    /// The `TapFormatter` (`tap`), which prints the Test Anything Protocol.
+    #[serde(rename = "tap")]
+    Tap,
+    /// The `UsageFormatter` (`usage`), which writes `usage.txt` and `usage.json`
+    /// to the output directory.
+    #[serde(rename = "usage")]
+    Usage,
+}
+
+impl FromStr for Formatter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Formatter, String> {
+        match s {
+            "pretty" => Ok(Formatter::Pretty),
+            "progress" => Ok(Formatter::ProgressBar),
+            "rerun" => Ok(Formatter::Rerun),
+            "json" => Ok(Formatter::Json),
+            "junit" => Ok(Formatter::JUnit),
+            "ndjson" => Ok(Formatter::Ndjson),
+            "html" => Ok(Formatter::Html),
+            "tap" => Ok(Formatter::Tap),
+            "usage" => Ok(Formatter::Usage),
+            _ => Err("expected one of: pretty, progress, rerun, json, junit, ndjson, html, tap, usage"
+                .to_owned()),
+        }
+    }
+}
+
+/// The variant of an `ExecutionMode`, without its event listeners.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExecutionModeKind {
+    ParallelScenarios,
+    ParallelFeatures,
+    Sequential,
+}
+
+impl FromStr for ExecutionModeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ExecutionModeKind, String> {
+        match s {
+            "parallel-scenarios" => Ok(ExecutionModeKind::ParallelScenarios),
+            "parallel-features" => Ok(ExecutionModeKind::ParallelFeatures),
+            "sequential" => Ok(ExecutionModeKind::Sequential),
+            _ => Err("expected one of: parallel-scenarios, parallel-features, sequential".to_owned()),
+        }
+    }
+}
+
+impl ConfigOverrides {
+    /// Reads the overrides from the config file, the environment variables
+    /// and the arguments of the current process.
+    pub fn load() -> Result<ConfigOverrides, Error> {
+        let mut overrides = ConfigFile::load()?
+            .map(ConfigOverrides::from)
+            .unwrap_or_default();
+        overrides.add_env()?;
+        overrides.add_args(env::args().skip(1))?;
+        Ok(overrides)
+    }
+
+    /// Adds the overrides of the environment variables, replacing the ones of the config file.
+    fn add_env(&mut self) -> Result<(), Error> {
+        if let Some(tags) = read_env_var(TAGS_ENV_VAR)? {
+            self.tags = Some(vec![tags]);
+        }
+        if let Some(name) = read_env_var(NAME_ENV_VAR)? {
+            self.names = Some(vec![name]);
+        }
+        if let Some(dry_run) = read_env_var(DRY_RUN_ENV_VAR)? {
+            self.dry_run = Some(parse_bool(DRY_RUN_ENV_VAR, &dry_run)?);
+        }
+        if let Some(strict) = read_env_var(STRICT_ENV_VAR)? {
+            self.strict = Some(parse_bool(STRICT_ENV_VAR, &strict)?);
+        }
+        if let Some(fail_fast) = read_env_var(FAIL_FAST_ENV_VAR)? {
+            self.fail_fast = Some(parse_bool(FAIL_FAST_ENV_VAR, &fail_fast)?);
+        }
+        if let Some(retry) = read_env_var(RETRY_ENV_VAR)? {
+            self.retry = Some(parse_retry(RETRY_ENV_VAR, &retry)?);
+        }
+        if let Some(order) = read_env_var(ORDER_ENV_VAR)? {
+            self.order = Some(parse_value(ORDER_ENV_VAR, &order)?);
+        }
+        if let Some(rerun_file) = read_env_var(RERUN_FILE_ENV_VAR)? {
+            self.rerun_file = Some(PathBuf::from(rerun_file));
+        }
+        if let Some(format) = read_env_var(FORMAT_ENV_VAR)? {
+            let formatters = format.split(',')
+                .map(str::trim)
+                .filter(|formatter| !formatter.is_empty())
+                .map(|formatter| parse_value(FORMAT_ENV_VAR, formatter))
+                .collect::<Result<Vec<Formatter>, Error>>()?;
+            self.formatters = Some(formatters);
+        }
+        if let Some(execution_mode) = read_env_var(EXECUTION_MODE_ENV_VAR)? {
+            self.execution_mode = Some(parse_value(EXECUTION_MODE_ENV_VAR, &execution_mode)?);
+        }
+        if let Some(threads) = read_env_var(THREADS_ENV_VAR)? {
+            self.num_threads = Some(parse_num_threads(THREADS_ENV_VAR, &threads)?);
+        }
+
+        Ok(())
+    }
+
+    /// Adds the overrides of the arguments, replacing the ones of the environment variables.
+    fn add_args<I: Iterator<Item=String>>(&mut self, mut args: I) -> Result<(), Error> {
+        let mut tags = Vec::new();
+        let mut names = Vec::new();
+        let mut formatters = Vec::new();
+
+        while let Some(arg) = args.next() {
+            // The value is either part of the argument (`--retry=2`) or the next argument.
+            let (name, mut value) = match arg.find('=') {
+                Some(index) if arg.starts_with("--") => (&arg[..index], Some(arg[index + 1..].to_owned())),
+                _ => (arg.as_str(), None),
+            };
+
+            match name {
+                "--tags" => tags.push(arg_value(name, &mut value, &mut args)?),
+                "--name" => names.push(arg_value(name, &mut value, &mut args)?),
+                "--dry-run" => self.dry_run = Some(true),
+                "--strict" => self.strict = Some(true),
+                "--no-strict" => self.strict = Some(false),
+                "--fail-fast" => self.fail_fast = Some(true),
+                "--retry" => self.retry = Some(parse_retry(name, &arg_value(name, &mut value, &mut args)?)?),
+                "--order" => self.order = Some(parse_value(name, &arg_value(name, &mut value, &mut args)?)?),
+                "--rerun-file" => {
+                    self.rerun_file = Some(PathBuf::from(arg_value(name, &mut value, &mut args)?));
+                },
+                "--cuke-format" => {
+                    formatters.push(parse_value(name, &arg_value(name, &mut value, &mut args)?)?);
+                },
+                "--execution-mode" => {
+                    self.execution_mode = Some(parse_value(name, &arg_value(name, &mut value, &mut args)?)?);
+                },
+                "--threads" => {
+                    self.num_threads = Some(parse_num_threads(name, &arg_value(name, &mut value, &mut args)?)?);
+                },
+                _ if TEST_HARNESS_OPTIONS.contains(&name) || !name.starts_with("--") => continue,
+                _ => return Err(Error::InvalidValue {
+                    setting: "the arguments".to_owned(),
+                    value: arg.clone(),
+                    message: "unknown option".to_owned(),
+                }),
+            }
+
+            if let Some(value) = value {
+                return Err(Error::InvalidValue {
+                    setting: name.to_owned(),
+                    value,
+                    message: "the argument does not take a value".to_owned(),
+                });
+            }
+        }
+
+        if !tags.is_empty() {
+            self.tags = Some(tags);
+        }
+        if !names.is_empty() {
+            self.names = Some(names);
+        }
+        if !formatters.is_empty() {
+            self.formatters = Some(formatters);
+        }
+
+        Ok(())
+    }
+
+    /// Applies the overrides to the config.
+    ///
+    /// If the execution mode is changed between the sequential and a parallel execution mode,
+    /// the event listeners of the config cannot be used anymore, so formatters must be selected too.
+    pub fn apply<'o, 'c: 'o>(&'o self, config: Config<'c>) -> Result<Config<'o>, Error> {
+        let mut config: Config<'o> = config;
+
+        if let Some(ref features) = self.features {
+            config.features = features.clone();
+        }
+        if let Some(ref exclude_features) = self.exclude_features {
+            config.exclude_features = exclude_features.clone();
+        }
+        if let Some(ref output_dir) = self.output_dir {
+            config.output_dir = output_dir;
+        }
+        if let Some(ref tags) = self.tags {
+            config.tags = tags.clone();
+        }
+        if let Some(ref names) = self.names {
+            config.names = names.clone();
+        }
+        if let Some(dry_run) = self.dry_run {
+            config.dry_run = dry_run;
+        }
+        if let Some(strict) = self.strict {
+            config.strict = strict;
+        }
+        if let Some(fail_fast) = self.fail_fast {
+            config.fail_fast = fail_fast;
+        }
+        if let Some(retry) = self.retry {
+            config.retry = retry;
+        }
+        if let Some(order) = self.order {
+            config.order = order;
+        }
+        if let Some(ref rerun_file) = self.rerun_file {
+            config.rerun_file = Some(rerun_file);
+        }
+
+        let execution_mode_kind = self.execution_mode
+            .unwrap_or_else(|| ExecutionModeKind::from(&config.execution_mode));
+        config.execution_mode = if let Some(ref formatters) = self.formatters {
+            if execution_mode_kind != ExecutionModeKind::Sequential && formatters.contains(&Formatter::Pretty) {
+                return Err(Error::Conflict(format!("the pretty formatter is only supported \
+                    by the sequential execution mode, not by the {:?} execution mode", execution_mode_kind)));
+            }
+            execution_mode_kind.without_event_listeners()
+        } else {
+            execution_mode_kind.with_event_listeners_of(config.execution_mode)?
+        };
+
+        Ok(config)
+    }
+}
+
+impl From<ConfigFile> for ConfigOverrides {
+    fn from(config_file: ConfigFile) -> ConfigOverrides {
+        ConfigOverrides {
+            features: config_file.features,
+            exclude_features: config_file.exclude_features,
+            output_dir: config_file.output_dir,
+            tags: config_file.tags,
+            strict: config_file.strict,
+            fail_fast: config_file.fail_fast,
+            retry: config_file.retry,
+            order: config_file.order,
+            rerun_file: config_file.rerun_file,
+            formatters: config_file.formatters,
+            execution_mode: config_file.execution_mode,
+            ..ConfigOverrides::default()
+        }
+    }
+}
+
+impl<'a, 'c> From<&'a ExecutionMode<'c>> for ExecutionModeKind {
+    fn from(execution_mode: &'a ExecutionMode<'c>) -> ExecutionModeKind {
+        match execution_mode {
+            ExecutionMode::ParallelScenarios { .. } => ExecutionModeKind::ParallelScenarios,
+            ExecutionMode::ParallelFeatures { .. } => ExecutionModeKind::ParallelFeatures,
+            ExecutionMode::Sequential { .. } => ExecutionModeKind::Sequential,
+        }
+    }
+}
+
+impl ExecutionModeKind {
+    fn without_event_listeners<'c>(self) -> ExecutionMode<'c> {
+        match self {
+            ExecutionModeKind::ParallelScenarios => ExecutionMode::ParallelScenarios { event_listeners: &[] },
+            ExecutionModeKind::ParallelFeatures => ExecutionMode::ParallelFeatures { event_listeners: &[] },
+            ExecutionModeKind::Sequential => ExecutionMode::Sequential { event_listeners: &[] },
+        }
+    }
+
+    fn with_event_listeners_of<'c>(self, execution_mode: ExecutionMode<'c>) -> Result<ExecutionMode<'c>, Error> {
+        let sync_event_listeners = match execution_mode {
+            ExecutionMode::ParallelScenarios { event_listeners } => event_listeners,
+            ExecutionMode::ParallelFeatures { event_listeners } => event_listeners,
+            ExecutionMode::Sequential { event_listeners } => {
+                return match self {
+                    ExecutionModeKind::Sequential => Ok(ExecutionMode::Sequential { event_listeners }),
+                    _ => Err(Error::Conflict(format!("the event listeners of the sequential execution mode \
+                        may not be thread safe, select formatters (with {} or --cuke-format) \
+                        for the {:?} execution mode", FORMAT_ENV_VAR, self))),
+                };
+            },
+        };
+
+        match self {
+            ExecutionModeKind::ParallelScenarios => {
+                Ok(ExecutionMode::ParallelScenarios { event_listeners: sync_event_listeners })
+            },
+            ExecutionModeKind::ParallelFeatures => {
+                Ok(ExecutionMode::ParallelFeatures { event_listeners: sync_event_listeners })
+            },
+            ExecutionModeKind::Sequential => {
+                Err(Error::Conflict(format!("the event listeners of a parallel execution mode \
+                    cannot be used by the sequential execution mode, select formatters \
+                    (with {} or --cuke-format) as well", FORMAT_ENV_VAR)))
+            },
+        }
+    }
+}
+
+fn read_env_var(name: &'static str) -> Result<Option<String>, Error> {
+    match env::var(name) {
+        Ok(value) => Ok(Some(value)),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(err) => Err(Error::EnvVar(err, name)),
+    }
+}
+
+/// Takes the value that is part of the argument, or else the next argument.
+fn arg_value<I: Iterator<Item=String>>(arg: &str, value: &mut Option<String>, args: &mut I)
+    -> Result<String, Error>
+{
+    value.take().or_else(|| args.next()).ok_or_else(|| Error::InvalidValue {
+        setting: arg.to_owned(),
+        value: String::new(),
+        message: "a value is required".to_owned(),
+    })
+}
+
+fn parse_value<T: FromStr<Err=String>>(setting: &str, value: &str) -> Result<T, Error> {
+    value.parse().map_err(|message| Error::InvalidValue {
+        setting: setting.to_owned(),
+        value: value.to_owned(),
+        message,
+    })
+}
+
+fn parse_bool(setting: &str, value: &str) -> Result<bool, Error> {
+    match value.to_lowercase().as_str() {
+        "true" | "1" | "yes" => Ok(true),
+        "false" | "0" | "no" => Ok(false),
+        _ => Err(Error::InvalidValue {
+            setting: setting.to_owned(),
+            value: value.to_owned(),
+            message: "expected one of: true, false, 1, 0, yes, no".to_owned(),
+        }),
+    }
+}
+
+fn parse_num_threads(setting: &str, value: &str) -> Result<usize, Error> {
+    match value.parse::<usize>() {
+        Ok(num_threads) if num_threads > 0 => Ok(num_threads),
+        _ => Err(Error::InvalidValue {
+            setting: setting.to_owned(),
+            value: value.to_owned(),
+            message: "expected a positive number".to_owned(),
+        }),
+    }
+}
+
+fn parse_retry(setting: &str, value: &str) -> Result<u32, Error> {
+    value.parse::<u32>().map_err(|_| Error::InvalidValue {
+        setting: setting.to_owned(),
+        value: value.to_owned(),
+        message: "expected the number of retries".to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> impl Iterator<Item=String> {
+        args.iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<String>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn args_override_settings() {
+        let mut overrides = ConfigOverrides::default();
+        overrides.add_args(args(&["--tags", "@wip", "--tags", "not @slow", "--name", "^Add",
+            "--dry-run", "--no-strict", "--fail-fast", "--retry", "2", "--order", "random:42",
+            "--rerun-file", "target/cukes/rerun.txt", "--cuke-format", "progress",
+            "--execution-mode", "parallel-features", "--threads", "4"])).unwrap();
+
+        assert_eq!(overrides, ConfigOverrides {
+            features: None,
+            exclude_features: None,
+            output_dir: None,
+            tags: Some(vec!["@wip".to_owned(), "not @slow".to_owned()]),
+            names: Some(vec!["^Add".to_owned()]),
+            dry_run: Some(true),
+            strict: Some(false),
+            fail_fast: Some(true),
+            retry: Some(2),
+            order: Some(Order::Random { seed: Some(42) }),
+            rerun_file: Some(PathBuf::from("target/cukes/rerun.txt")),
+            formatters: Some(vec![Formatter::ProgressBar]),
+            execution_mode: Some(ExecutionModeKind::ParallelFeatures),
+            num_threads: Some(4),
+        });
+    }
+
+    #[test]
+    fn arg_values_can_be_part_of_the_arg() {
+        let mut overrides = ConfigOverrides::default();
+        overrides.add_args(args(&["--tags=@wip and not @slow", "--retry=2", "--cuke-format=json",
+            "--cuke-format", "junit"])).unwrap();
+
+        assert_eq!(overrides, ConfigOverrides {
+            tags: Some(vec!["@wip and not @slow".to_owned()]),
+            retry: Some(2),
+            formatters: Some(vec![Formatter::Json, Formatter::JUnit]),
+            ..ConfigOverrides::default()
+        });
+    }
+
+    #[test]
+    fn test_harness_args_are_ignored() {
+        let mut overrides = ConfigOverrides::default();
+        overrides.add_args(args(&["--nocapture", "--format=terse", "--format", "json",
+            "--test-threads", "1", "-q", "test_cucumber_features"])).unwrap();
+
+        assert_eq!(overrides, ConfigOverrides::default());
+    }
+
+    #[test]
+    fn unknown_options_are_rejected() {
+        assert!(ConfigOverrides::default().add_args(args(&["--tag", "@wip"])).is_err());
+        assert!(ConfigOverrides::default().add_args(args(&["--cuke-fromat", "json"])).is_err());
+        assert!(ConfigOverrides::default().add_args(args(&["--fail-fats"])).is_err());
+    }
+
+    #[test]
+    fn invalid_arg_values_are_rejected() {
+        assert!(ConfigOverrides::default().add_args(args(&["--threads", "0"])).is_err());
+        assert!(ConfigOverrides::default().add_args(args(&["--cuke-format", "xml"])).is_err());
+        assert!(ConfigOverrides::default().add_args(args(&["--tags"])).is_err());
+        assert!(ConfigOverrides::default().add_args(args(&["--retry="])).is_err());
+        assert!(ConfigOverrides::default().add_args(args(&["--dry-run=false"])).is_err());
+    }
+}
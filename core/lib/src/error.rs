@@ -26,7 +26,12 @@ pub enum Error {
     FromScenario(#[cause] glue::scenario::FromScenarioError),
     /// An error that occurred while executing a step or hook function.
     Execution(#[cause] ::glue::error::ExecutionError),
-    AmbiguousStepDefinitions,
+    /// A step matched more than one step definition.
+    AmbiguousStepDefinitions {
+        step_text: String,
+        /// The pattern and location of every matching step definition.
+        step_definitions: Vec<(String, glue::CodeLocation)>,
+    },
     UndefinedStepDefinition,
     Pending,
     /// Hints that destructuring should not be exhaustive.
@@ -83,7 +88,13 @@ impl fmt::Display for Error {
             Error::Gherkin(ref err) => write!(f, "{}", err),
             Error::FromScenario(ref err) => write!(f, "{}", err),
             Error::Execution(ref err) => write!(f, "{}", err),
-            Error::AmbiguousStepDefinitions => write!(f, "AmbiguousStepDefinitions"),
+            Error::AmbiguousStepDefinitions { ref step_text, ref step_definitions } => {
+                write!(f, "step \"{}\" matches more than one step definition:", step_text)?;
+                for (pattern, location) in step_definitions {
+                    write!(f, "\n  \"{}\" in {}", pattern, location)?;
+                }
+                Ok(())
+            },
             Error::UndefinedStepDefinition => write!(f, "UndefinedStepDefinition"),
             Error::Pending => write!(f, "TODO: implement me"),
             Error::__Nonexhaustive => unreachable!(),
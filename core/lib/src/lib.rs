@@ -14,12 +14,20 @@ extern crate failure;
 extern crate failure_derive;
 extern crate gherkin;
 extern crate walkdir;
+extern crate glob;
 extern crate regex;
 extern crate rayon;
 extern crate termcolor;
 extern crate indicatif;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate toml;
+#[macro_use]
+extern crate serde_json;
+extern crate base64;
 
-pub use config::{Config, ExecutionMode};
+pub use config::{Config, ExecutionMode, Order};
 pub use error::{Error, Result};
 pub use runtime::Glue;
 pub use runtime::event_listener;
@@ -30,6 +38,34 @@ pub mod api;
 mod runner;
 mod runtime;
 
+/// Marks the current step or hook as pending (not implemented yet).
+///
+/// This stops the execution of the step or hook function like a panic,
+/// but the step is reported as `Pending` instead of `Failed`.
+/// The remaining steps of the scenario are skipped.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// #[when("I divide {int} by {int}")]
+/// pub fn divide(calc: &mut Calc, dividend: i32, divisor: i32) {
+///     pending!("division is not supported yet");
+/// }
+/// ```
+#[macro_export]
+macro_rules! pending {
+    () => (
+        ::std::panic::resume_unwind(Box::new(
+            <$crate::glue::error::PendingError as ::std::default::Default>::default()
+        ))
+    );
+    ($($arg:tt)+) => (
+        ::std::panic::resume_unwind(Box::new(
+            $crate::glue::error::PendingError::new(format!($($arg)+))
+        ))
+    );
+}
+
 pub fn execute_cucumber_tests(glue: Glue, config: Config) {
     let exit_status = runtime::run(glue, config);
 
@@ -5,31 +5,244 @@ pub use self::test_step::{HookTestStep, CukeStepTestStep};
 mod event;
 mod test_step;
 
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+
+use gherkin::ast::Feature;
 use gherkin::cuke::{Cuke, Tag};
 
-use api::HookType;
+use Config;
+use api::{HookType, HookDefinitionInfo, StepDefinitionInfo, TestResultStatus};
+use api::event::Event;
+use glue;
+use glue::step::cucumber_expression;
+use glue::scenario::FeatureData;
 use runtime::{Glue, HookDefinition};
 use runtime::{self, TestCase, StepDefinitionMatch, HookDefinitionMatch};
 
+const RETRY_TAG_PREFIX: &str = "@retry(";
+
 pub struct Runner {
     glue: Glue,
     dry_run: bool,
+    strict: bool,
+    fail_fast: bool,
+    retry: u32,
+    /// Set after the first failure in fail fast mode, or when a `BeforeAll` hook does not pass,
+    /// so the remaining test cases are skipped.
+    stopped: AtomicBool,
 }
 
 impl Runner {
-    pub fn new(glue: Glue, dry_run: bool) -> Runner {
+    pub fn new(glue: Glue, config: &Config) -> Runner {
         Runner {
             glue,
-            dry_run,
+            dry_run: config.dry_run,
+            strict: config.strict,
+            fail_fast: config.fail_fast,
+            retry: config.retry,
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    /// Runs the cuke and runs it again (with a fresh scenario) as long as it fails
+    /// and retries are left.
+    ///
+    /// If the feature is skipped (see `run_feature`), the cuke is reported as skipped.
+    pub fn run<EP: EventPublisher>(&self, uri: &str, cuke: Cuke, feature_data: &Arc<FeatureData>,
+        feature_skipped: bool, event_publisher: &EP)
+    {
+        // invalid retry tags are rejected by `check_retry_tags` before the run
+        let retries = self.get_retries(&cuke).unwrap_or(self.retry);
+        let mut attempt = 0;
+
+        loop {
+            let not_run = feature_skipped || self.is_stopped();
+            let skip = self.dry_run || not_run;
+            let test_case = self.create_test_case(uri, &cuke, feature_data, skip, not_run, attempt, retries);
+            let (status, will_be_retried) = runtime::test_case::run(test_case, event_publisher);
+
+            if will_be_retried {
+                attempt += 1;
+                continue;
+            }
+
+            if !status.is_ok(self.strict) {
+                self.stop_if_fail_fast();
+            }
+            return;
+        }
+    }
+
+    /// Checks the `@retry(n)` tags of the cukes, so an invalid tag stops the run
+    /// before any hook is run.
+    pub fn check_retry_tags<'a, 'c: 'a, I: IntoIterator<Item=&'a Cuke<'c>>>(&self, cukes: I) {
+        for cuke in cukes {
+            if let Err(tag_name) = self.get_retries(cuke) {
+                panic!("retry tag \"{}\" of \"{}\" is invalid: \
+                    expected @retry(n) with the number of retries n", tag_name, cuke.name);
+            }
+        }
+    }
+
+    /// The number of retries of a failed cuke, where a `@retry(n)` tag overrides `Config::retry`,
+    /// or the name of an invalid retry tag.
+    fn get_retries(&self, cuke: &Cuke) -> Result<u32, String> {
+        let mut retries = self.retry;
+
+        for tag in &cuke.tags {
+            match parse_retry_tag(tag.name) {
+                Some(Ok(tag_retries)) => retries = tag_retries,
+                Some(Err(())) => return Err(tag.name.to_string()),
+                None => {},
+            }
+        }
+
+        Ok(retries)
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    fn stop_if_fail_fast(&self) {
+        if self.fail_fast {
+            self.stopped.store(true, Ordering::SeqCst);
         }
     }
 
-    pub fn run<EP: EventPublisher>(&self, uri: &str, cuke: Cuke, event_publisher: &EP) {
-        let test_case = self.create_test_case(uri, &cuke);
-        runtime::test_case::run(test_case, event_publisher);
+    /// Sends the `GlueLoaded` event with the step definitions and hooks of the glue code.
+    pub fn send_glue_loaded<EP: EventPublisher>(&self, event_publisher: &EP) {
+        let mut step_definitions = self.glue.get_step_definitions_by_pattern().values()
+            .map(|step_definition| StepDefinitionInfo {
+                expression: step_definition.get_pattern(),
+                is_regex: cucumber_expression::is_regex(step_definition.get_pattern()),
+                location: step_definition.get_location(),
+            })
+            .collect::<Vec<StepDefinitionInfo>>();
+        step_definitions.sort_by_key(|step_definition|
+            (step_definition.location.file_path, step_definition.location.line_number));
+
+        let hooks_by_type = [
+            (HookType::BeforeAll, self.glue.get_before_all_hooks()),
+            (HookType::BeforeFeature, self.glue.get_before_feature_hooks()),
+            (HookType::BeforeScenario, self.glue.get_before_scenario_hooks()),
+            (HookType::BeforeStep, self.glue.get_before_step_hooks()),
+            (HookType::AfterStep, self.glue.get_after_step_hooks()),
+            (HookType::AfterScenario, self.glue.get_after_scenario_hooks()),
+            (HookType::AfterFeature, self.glue.get_after_feature_hooks()),
+            (HookType::AfterAll, self.glue.get_after_all_hooks()),
+        ];
+        let hook_definitions = hooks_by_type.iter()
+            .flat_map(|&(hook_type, hooks)| hooks.iter().map(move |hook| HookDefinitionInfo {
+                hook_type,
+                tag_expression: hook.get_tag_expression(),
+                location: hook.get_location(),
+            }))
+            .collect::<Vec<HookDefinitionInfo>>();
+
+        event_publisher.send(Event::GlueLoaded {
+            time: SystemTime::now(),
+            step_definitions: &step_definitions,
+            hook_definitions: &hook_definitions,
+        });
     }
 
-    fn create_test_case<'c, 's: 'c>(&'s self, uri: &'c str, cuke: &'c Cuke) -> TestCase<'c> {
+    /// Runs the `BeforeAll` hooks until the first hook that does not pass.
+    ///
+    /// If a hook does not pass, the run is stopped, so all cukes are reported as skipped.
+    pub fn run_before_all_hooks<EP: EventPublisher>(&self, event_publisher: &EP,
+        glue_scenario: &mut glue::scenario::Scenario)
+    {
+        if self.dry_run {
+            return;
+        }
+
+        let hooks = self.create_hooks(&[], self.glue.get_before_all_hooks(), HookType::BeforeAll);
+        let hooks_passed = hooks.iter()
+            .map(|hook| hook.run_global(event_publisher, glue_scenario))
+            .all(|hook_result| hook_result.status == TestResultStatus::Passed);
+
+        if !hooks_passed {
+            self.stopped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Runs all `AfterAll` hooks, regardless of the results of the test cases.
+    pub fn run_after_all_hooks<EP: EventPublisher>(&self, event_publisher: &EP,
+        glue_scenario: &mut glue::scenario::Scenario)
+    {
+        if self.dry_run {
+            return;
+        }
+
+        let hooks = self.create_hooks(&[], self.glue.get_after_all_hooks(), HookType::AfterAll);
+        for hook in &hooks {
+            hook.run_global(event_publisher, glue_scenario);
+        }
+    }
+
+    /// Runs the cukes of a feature file (with `run_cukes`) between the `BeforeFeature`
+    /// and `AfterFeature` hooks that match the tags of the feature.
+    ///
+    /// The cukes get read-only access to the feature data that was set by the `BeforeFeature`
+    /// hooks. `run_cukes` is told whether the feature is skipped, which is the case if a
+    /// `BeforeFeature` hook did not pass, or if the run is already stopped (then the hooks
+    /// are not run at all).
+    pub fn run_feature<EP, F>(&self, uri: &str, feature: &Feature, event_publisher: &EP, run_cukes: F)
+        where EP: EventPublisher, F: FnOnce(&Arc<FeatureData>, bool)
+    {
+        let mut feature_scenario = glue::scenario::Scenario::new();
+
+        if self.is_stopped() {
+            run_cukes(&feature_scenario.share_feature_data(), true);
+            return;
+        }
+
+        let hooks_passed = self.run_before_feature_hooks(uri, feature, event_publisher, &mut feature_scenario);
+        if !hooks_passed {
+            self.stop_if_fail_fast();
+        }
+        run_cukes(&feature_scenario.share_feature_data(), !hooks_passed);
+
+        self.run_after_feature_hooks(uri, feature, event_publisher, &mut feature_scenario);
+    }
+
+    fn run_before_feature_hooks<EP: EventPublisher>(&self, uri: &str, feature: &Feature,
+        event_publisher: &EP, glue_scenario: &mut glue::scenario::Scenario) -> bool
+    {
+        if self.dry_run {
+            return true;
+        }
+
+        let hooks = self.create_feature_hooks(feature, self.glue.get_before_feature_hooks(),
+            HookType::BeforeFeature);
+        hooks.iter()
+            .map(|hook| hook.run_feature(event_publisher, uri, feature, glue_scenario))
+            .all(|hook_result| hook_result.status == TestResultStatus::Passed)
+    }
+
+    fn run_after_feature_hooks<EP: EventPublisher>(&self, uri: &str, feature: &Feature,
+        event_publisher: &EP, glue_scenario: &mut glue::scenario::Scenario)
+    {
+        if self.dry_run {
+            return;
+        }
+
+        let hooks = self.create_feature_hooks(feature, self.glue.get_after_feature_hooks(),
+            HookType::AfterFeature);
+        for hook in &hooks {
+            let hook_result = hook.run_feature(event_publisher, uri, feature, glue_scenario);
+            if hook_result.status != TestResultStatus::Passed {
+                self.stop_if_fail_fast();
+            }
+        }
+    }
+
+    fn create_test_case<'c, 's: 'c>(&'s self, uri: &'c str, cuke: &'c Cuke,
+        feature_data: &Arc<FeatureData>, skip: bool, not_run: bool, attempt: u32, retries: u32) -> TestCase<'c>
+    {
         let (
             before_hooks,
             after_hooks,
@@ -55,7 +268,11 @@ impl Runner {
             test_steps,
             before_hooks,
             after_hooks,
-            dry_run: self.dry_run,
+            feature_data: feature_data.clone(),
+            skip,
+            not_run,
+            attempt,
+            retries,
         }
     }
 
@@ -76,20 +293,26 @@ impl Runner {
 
         for hook_definition in hook_definitions {
             if hook_definition.matches(tags) {
-                let test_step = HookTestStep {
-                    definition_match: StepDefinitionMatch::Hook(HookDefinitionMatch {
-                        hook_definition: hook_definition.clone(),
-                        arguments: Vec::new(),
-                    }),
-                    hook_type,
-                };
-                hooks.push(test_step);
+                hooks.push(create_hook_test_step(hook_definition, hook_type));
             }
         }
 
         hooks
     }
 
+    fn create_feature_hooks(&self, feature: &Feature, hook_definitions: &[HookDefinition],
+        hook_type: HookType) -> Vec<HookTestStep>
+    {
+        let tag_names = feature.tags.iter()
+            .map(|tag| tag.name.as_ref())
+            .collect::<Vec<&str>>();
+
+        hook_definitions.iter()
+            .filter(|hook_definition| hook_definition.matches_tag_names(&tag_names))
+            .map(|hook_definition| create_hook_test_step(hook_definition, hook_type))
+            .collect()
+    }
+
     fn create_test_steps<'e, 's: 'e>(&'s self, uri: &'s str, cuke: &'e Cuke)
         -> Vec<CukeStepTestStep<'e>>
     {
@@ -138,3 +361,387 @@ impl Runner {
         self.create_hooks(tags, hook_definitions, HookType::AfterStep)
     }
 }
+
+fn create_hook_test_step<'s>(hook_definition: &HookDefinition, hook_type: HookType) -> HookTestStep<'s> {
+    HookTestStep {
+        definition_match: StepDefinitionMatch::Hook(HookDefinitionMatch {
+            hook_definition: hook_definition.clone(),
+            arguments: Vec::new(),
+        }),
+        hook_type,
+    }
+}
+
+/// Parses the number of retries of a `@retry(n)` tag, or returns `None` for other tags.
+fn parse_retry_tag(tag_name: &str) -> Option<Result<u32, ()>> {
+    if !tag_name.starts_with(RETRY_TAG_PREFIX) {
+        return None;
+    }
+
+    let retries = &tag_name[RETRY_TAG_PREFIX.len()..];
+    if !retries.ends_with(')') {
+        return Some(Err(()));
+    }
+
+    Some(retries[..retries.len() - 1].parse().map_err(|_| ()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::sync::atomic::AtomicUsize;
+
+    use api;
+    use api::event::EventListener;
+    use glue::{CodeLocation, StaticGlueDefinitions};
+    use glue::error::ExecutionError;
+    use glue::hook::StaticHookDef;
+    use glue::step::{StaticStepDef, StepKeyword};
+    use glue::step::argument::StepArgument;
+    use runtime::event_listener::{ExitStatusListener, TestSummaryListener};
+
+    use super::*;
+
+    /// Records the results of the steps and test cases, and passes the events on to the listeners.
+    #[derive(Debug, Default)]
+    struct EventRecorder<'l> {
+        events: RefCell<Vec<String>>,
+        listeners: Vec<&'l EventListener>,
+    }
+
+    impl<'l> EventPublisher for EventRecorder<'l> {
+        fn send(&self, event: Event) {
+            for listener in &self.listeners {
+                listener.on_event(&event);
+            }
+
+            let recorded_event = match event {
+                Event::TestStepFinished { test_step: api::TestStep::Cuke(cuke_step), result, .. } => {
+                    format!("step \"{}\" {}", cuke_step.get_step_text(), result.status)
+                },
+                Event::FeatureHookFinished { test_step: api::TestStep::Hook(hook), result, .. } => {
+                    format!("{:?} hook {}", hook.get_hook_type(), result.status)
+                },
+                Event::SnippetsSuggested { step_line, .. } => format!("snippet for line {}", step_line),
+                Event::TestCaseFinished { test_case, result, attempt, will_be_retried, .. } => {
+                    let mut recorded_event = format!("test case \"{}\" {}", test_case.get_name(), result.status);
+                    if attempt > 0 {
+                        recorded_event.push_str(&format!(" (attempt {})", attempt));
+                    }
+                    if will_be_retried {
+                        recorded_event.push_str(" (will be retried)");
+                    }
+                    recorded_event
+                },
+                _ => return,
+            };
+            self.events.borrow_mut().push(recorded_event);
+        }
+    }
+
+    fn failing_step(_scenario: &mut glue::scenario::Scenario, _arguments: &[StepArgument])
+        -> ::std::result::Result<(), ExecutionError>
+    {
+        Err(glue::error::panic_error(Box::new("failed")))
+    }
+
+    fn passing_step(_scenario: &mut glue::scenario::Scenario, _arguments: &[StepArgument])
+        -> ::std::result::Result<(), ExecutionError>
+    {
+        Ok(())
+    }
+
+    static FAILING_STEP: StaticStepDef = StaticStepDef {
+        name: "failing_step",
+        keyword: StepKeyword::Given,
+        expression: "a failing step",
+        step_fn: failing_step,
+        location: CodeLocation { file_path: "steps.rs", line_number: 1 },
+    };
+
+    static PASSING_STEP: StaticStepDef = StaticStepDef {
+        name: "passing_step",
+        keyword: StepKeyword::Given,
+        expression: "a passing step",
+        step_fn: passing_step,
+        location: CodeLocation { file_path: "steps.rs", line_number: 6 },
+    };
+
+    /// The number of calls of the flaky step, which only fails on the first call.
+    static FLAKY_STEP_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn flaky_step(_scenario: &mut glue::scenario::Scenario, _arguments: &[StepArgument])
+        -> ::std::result::Result<(), ExecutionError>
+    {
+        if FLAKY_STEP_CALLS.fetch_add(1, Ordering::SeqCst) == 0 {
+            Err(glue::error::panic_error(Box::new("failed")))
+        } else {
+            Ok(())
+        }
+    }
+
+    static FLAKY_STEP: StaticStepDef = StaticStepDef {
+        name: "flaky_step",
+        keyword: StepKeyword::Given,
+        expression: "a flaky step",
+        step_fn: flaky_step,
+        location: CodeLocation { file_path: "steps.rs", line_number: 16 },
+    };
+
+    /// Feature data that is set by a `BeforeFeature` hook.
+    struct Greeting(&'static str);
+
+    fn feature_data_step(scenario: &mut glue::scenario::Scenario, _arguments: &[StepArgument])
+        -> ::std::result::Result<(), ExecutionError>
+    {
+        match scenario.get_feature_data::<Greeting>() {
+            Some(greeting) if greeting.0 == "hello" => Ok(()),
+            _ => Err(glue::error::panic_error(Box::new("feature data is missing"))),
+        }
+    }
+
+    static FEATURE_DATA_STEP: StaticStepDef = StaticStepDef {
+        name: "feature_data_step",
+        keyword: StepKeyword::Then,
+        expression: "the feature data is set",
+        step_fn: feature_data_step,
+        location: CodeLocation { file_path: "steps.rs", line_number: 11 },
+    };
+
+    static STEPS: &[&StaticStepDef] = &[&FAILING_STEP, &PASSING_STEP, &FLAKY_STEP, &FEATURE_DATA_STEP];
+
+    fn failing_hook(_scenario: &mut glue::scenario::Scenario) -> ::std::result::Result<(), ExecutionError> {
+        Err(glue::error::panic_error(Box::new("failed")))
+    }
+
+    fn passing_hook(_scenario: &mut glue::scenario::Scenario) -> ::std::result::Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn feature_data_hook(scenario: &mut glue::scenario::Scenario) -> ::std::result::Result<(), ExecutionError> {
+        scenario.set_feature_data(Greeting("hello"));
+        Ok(())
+    }
+
+    static FAILING_HOOK: StaticHookDef = StaticHookDef {
+        name: "failing_hook",
+        order: 0,
+        tag_expression: "",
+        hook_fn: failing_hook,
+        location: CodeLocation { file_path: "hooks.rs", line_number: 1 },
+    };
+
+    static PASSING_HOOK: StaticHookDef = StaticHookDef {
+        name: "passing_hook",
+        order: 0,
+        tag_expression: "",
+        hook_fn: passing_hook,
+        location: CodeLocation { file_path: "hooks.rs", line_number: 6 },
+    };
+
+    static FEATURE_DATA_HOOK: StaticHookDef = StaticHookDef {
+        name: "feature_data_hook",
+        order: 0,
+        tag_expression: "",
+        hook_fn: feature_data_hook,
+        location: CodeLocation { file_path: "hooks.rs", line_number: 11 },
+    };
+
+    static FAILING_HOOKS: &[&StaticHookDef] = &[&FAILING_HOOK];
+    static PASSING_HOOKS: &[&StaticHookDef] = &[&PASSING_HOOK];
+    static FEATURE_DATA_HOOKS: &[&StaticHookDef] = &[&FEATURE_DATA_HOOK];
+
+    fn step_definitions() -> StaticGlueDefinitions {
+        StaticGlueDefinitions {
+            parameter_types: &[],
+            before_all_hooks: &[],
+            before_feature_hooks: &[],
+            before_scenario_hooks: &[],
+            before_step_hooks: &[],
+            steps: STEPS,
+            after_step_hooks: &[],
+            after_scenario_hooks: &[],
+            after_feature_hooks: &[],
+            after_all_hooks: &[],
+        }
+    }
+
+    fn create_runner(static_glue_definitions: StaticGlueDefinitions, fail_fast: bool) -> Runner {
+        Runner {
+            glue: Glue::from(&[static_glue_definitions][..]),
+            dry_run: false,
+            strict: false,
+            fail_fast,
+            retry: 0,
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    /// Runs the cukes of the feature like the sequential execution mode and returns the recorded events.
+    fn run_feature(runner: &Runner, source: &str) -> Vec<String> {
+        run_feature_with_listeners(runner, source, Vec::new())
+    }
+
+    fn run_feature_with_listeners(runner: &Runner, source: &str, listeners: Vec<&EventListener>) -> Vec<String> {
+        let document = gherkin::Parser::default().parse_str(source)
+            .unwrap_or_else(|err| panic!("invalid feature: {}", err));
+        let feature = document.feature.as_ref().expect("feature");
+        let cukes = gherkin::cuke::Compiler::default().compile(&document);
+        let event_recorder = EventRecorder {
+            events: RefCell::new(Vec::new()),
+            listeners,
+        };
+
+        runner.run_feature("test.feature", feature, &event_recorder, |feature_data, feature_skipped| {
+            for cuke in cukes {
+                runner.run("test.feature", cuke, feature_data, feature_skipped, &event_recorder);
+            }
+        });
+
+        event_recorder.events.into_inner()
+    }
+
+    #[test]
+    fn cukes_are_skipped_if_a_before_all_hook_fails() {
+        let runner = create_runner(StaticGlueDefinitions {
+            before_all_hooks: FAILING_HOOKS,
+            ..step_definitions()
+        }, false);
+
+        let event_recorder = EventRecorder::default();
+        runner.run_before_all_hooks(&event_recorder, &mut glue::scenario::Scenario::new());
+        let events = run_feature(&runner, "\
+Feature: Before all
+
+  Scenario: Not run
+    Given a passing step
+");
+
+        assert_eq!(events, vec![
+            "step \"a passing step\" Skipped",
+            "test case \"Not run\" Skipped",
+        ]);
+    }
+
+    #[test]
+    fn cukes_can_read_the_feature_data_of_before_feature_hooks() {
+        let runner = create_runner(StaticGlueDefinitions {
+            before_feature_hooks: FEATURE_DATA_HOOKS,
+            after_feature_hooks: PASSING_HOOKS,
+            ..step_definitions()
+        }, false);
+
+        let events = run_feature(&runner, "\
+Feature: Feature data
+
+  Scenario: Reads feature data
+    Then the feature data is set
+");
+
+        assert_eq!(events, vec![
+            "BeforeFeature hook Passed",
+            "step \"the feature data is set\" Passed",
+            "test case \"Reads feature data\" Passed",
+            "AfterFeature hook Passed",
+        ]);
+    }
+
+    #[test]
+    fn cukes_are_skipped_if_a_before_feature_hook_fails() {
+        let runner = create_runner(StaticGlueDefinitions {
+            before_feature_hooks: FAILING_HOOKS,
+            after_feature_hooks: PASSING_HOOKS,
+            ..step_definitions()
+        }, false);
+
+        let events = run_feature(&runner, "\
+Feature: Before feature
+
+  Scenario: Not run
+    Given a passing step
+");
+
+        assert_eq!(events, vec![
+            "BeforeFeature hook Failed",
+            "step \"a passing step\" Skipped",
+            "test case \"Not run\" Skipped",
+            "AfterFeature hook Passed",
+        ]);
+    }
+
+    #[test]
+    fn cukes_after_a_fail_fast_stop_are_skipped_without_matching_their_steps() {
+        let runner = create_runner(step_definitions(), true);
+
+        let events = run_feature(&runner, "\
+Feature: Fail fast
+
+  Scenario: Failing
+    Given a failing step
+
+  Scenario: Not run
+    Given an undefined step
+    And a passing step
+");
+
+        assert_eq!(events, vec![
+            "step \"a failing step\" Failed",
+            "test case \"Failing\" Failed",
+            "step \"an undefined step\" Skipped",
+            "step \"a passing step\" Skipped",
+            "test case \"Not run\" Skipped",
+        ]);
+    }
+
+    #[test]
+    fn failed_cukes_are_retried_and_reported_as_flaky_if_they_pass() {
+        let runner = create_runner(step_definitions(), false);
+        let exit_status_listener = ExitStatusListener::new();
+        let test_summary_listener = TestSummaryListener::new();
+
+        let events = run_feature_with_listeners(&runner, "\
+Feature: Retry
+
+  @retry(1)
+  Scenario: Flaky
+    Given a flaky step
+", vec![&exit_status_listener, &test_summary_listener]);
+
+        assert_eq!(events, vec![
+            "step \"a flaky step\" Failed",
+            "test case \"Flaky\" Failed (will be retried)",
+            "step \"a flaky step\" Passed",
+            "test case \"Flaky\" Passed (attempt 1)",
+        ]);
+        assert_eq!(exit_status_listener.get_exit_status(false), 0);
+        assert_eq!(exit_status_listener.get_exit_status(true), 1, "a flaky cuke fails a strict run");
+        assert_eq!(test_summary_listener.get_flaky_scenarios(),
+            vec!["test.feature:4 # Flaky (passed after 2 attempts)"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "retry tag \"@retry(x)\" of \"Invalid\" is invalid")]
+    fn invalid_retry_tags_are_rejected() {
+        let runner = create_runner(step_definitions(), false);
+        let document = gherkin::Parser::default().parse_str("\
+Feature: Retry
+
+  @retry(x)
+  Scenario: Invalid
+    Given a passing step
+").unwrap();
+        let cukes = gherkin::cuke::Compiler::default().compile(&document);
+
+        runner.check_retry_tags(&cukes);
+    }
+
+    #[test]
+    fn retry_tag_is_parsed() {
+        assert_eq!(parse_retry_tag("@retry(3)"), Some(Ok(3)));
+        assert_eq!(parse_retry_tag("@retry(0)"), Some(Ok(0)));
+        assert_eq!(parse_retry_tag("@retry(x)"), Some(Err(())));
+        assert_eq!(parse_retry_tag("@retry(3"), Some(Err(())));
+        assert_eq!(parse_retry_tag("@retryable"), None);
+        assert_eq!(parse_retry_tag("@wip"), None);
+    }
+}
@@ -1,9 +1,12 @@
 use std::time::{SystemTime, Duration};
 
+use gherkin::ast::Feature;
 use gherkin::cuke;
 
 use error::{Result, Error};
 use api::{self, event::Event, HookType, CodeLocation, TestResult, TestResultStatus};
+use glue;
+use glue::error::ExecutionError;
 use glue::step::argument::StepArgument;
 use runner::EventPublisher;
 use runtime::{TestCase, StepDefinitionMatch, Scenario};
@@ -28,6 +31,67 @@ impl<'s> HookTestStep<'s> {
     }
 }
 
+impl<'s> HookTestStep<'s> {
+    /// Runs a global hook (`BeforeAll` or `AfterAll`) that does not belong to any test case.
+    pub fn run_global<EP: EventPublisher>(
+        &self,
+        event_publisher: &EP,
+        glue_scenario: &mut glue::scenario::Scenario,
+    ) -> TestResult
+    {
+        let (time, result) = self.execute(glue_scenario);
+        event_publisher.send(Event::GlobalHookFinished {
+            time,
+            test_step: &api::TestStep::Hook(self as &api::HookTestStep),
+            result: &result,
+        });
+        result
+    }
+
+    /// Runs a feature hook (`BeforeFeature` or `AfterFeature`) that belongs to a feature file
+    /// instead of a single test case.
+    pub fn run_feature<EP: EventPublisher>(
+        &self,
+        event_publisher: &EP,
+        uri: &str,
+        feature: &Feature,
+        glue_scenario: &mut glue::scenario::Scenario,
+    ) -> TestResult
+    {
+        let (time, result) = self.execute(glue_scenario);
+        event_publisher.send(Event::FeatureHookFinished {
+            time,
+            uri,
+            feature,
+            test_step: &api::TestStep::Hook(self as &api::HookTestStep),
+            result: &result,
+        });
+        result
+    }
+
+    fn execute(&self, glue_scenario: &mut glue::scenario::Scenario) -> (SystemTime, TestResult) {
+        let start_time = SystemTime::now();
+
+        let hook_result = match self.definition_match {
+            StepDefinitionMatch::Hook(ref hook_definition_match) => {
+                hook_definition_match.hook_definition.execute(glue_scenario)
+            },
+            _ => unreachable!("global and feature hook test steps always have a hook definition match"),
+        };
+        let (status, error) = match hook_result {
+            Ok(()) => (TestResultStatus::Passed, None),
+            Err(error) => (map_error_to_status(&error), Some(error)),
+        };
+
+        let stop_time = SystemTime::now();
+        let duration = match stop_time.duration_since(start_time) {
+            Ok(duration) => duration,
+            Err(system_time_error) => system_time_error.duration(),
+        };
+        (stop_time, map_status_to_result(status, error, duration))
+    }
+}
+
 impl<'s> api::HookTestStep<'s> for HookTestStep<'s> {
     fn get_code_location(&self) -> Option<&CodeLocation> {
         self.definition_match.get_location()
@@ -36,6 +100,15 @@ impl<'s> api::HookTestStep<'s> for HookTestStep<'s> {
     fn get_hook_type(&self) -> HookType {
         self.hook_type
     }
+
+    fn get_order(&self) -> isize {
+        match self.definition_match {
+            StepDefinitionMatch::Hook(ref hook_definition_match) => {
+                hook_definition_match.hook_definition.get_order()
+            },
+            _ => 0,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -91,7 +164,7 @@ impl<'s> api::CukeStepTestStep<'s> for CukeStepTestStep<'s> {
     }
 
     fn get_pattern(&self) -> Option<&str> {
-        self.step_definition_match.get_pattern().map(String::as_str)
+        self.step_definition_match.get_pattern()
     }
 
     fn get_cuke_step(&self) -> &cuke::Step {
@@ -145,7 +218,11 @@ fn run_test_step<EP: EventPublisher>(
         test_step,
     });
 
-    let step_result = execute_step(definition_match, scenario, skip);
+    let step_result = if test_case.not_run {
+        Ok(TestResultStatus::Skipped)
+    } else {
+        execute_step(definition_match, scenario, skip)
+    };
     let (status, error) = match step_result {
         Ok(test_result_type) => (test_result_type, None),
         Err(error) => (map_error_to_status(&error), Some(error)),
@@ -189,9 +266,10 @@ fn execute_step(
 
 fn map_error_to_status(error: &Error) -> TestResultStatus {
     match error {
-        Error::AmbiguousStepDefinitions => TestResultStatus::Ambiguous,
+        Error::AmbiguousStepDefinitions { .. } => TestResultStatus::Ambiguous,
         Error::UndefinedStepDefinition => TestResultStatus::Undefined,
         Error::Pending => TestResultStatus::Pending,
+        Error::Execution(ExecutionError::Pending(_)) => TestResultStatus::Pending,
         _ => TestResultStatus::Failed,
     }
 }
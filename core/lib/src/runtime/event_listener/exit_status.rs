@@ -1,11 +1,67 @@
 use std::sync::Mutex;
 use std::cell::RefCell;
 use api::event::{Event, EventListener};
-use api::TestResultStatus;
+use api::{HookType, TestResultStatus, TestStep};
+
+#[derive(Debug, Default)]
+struct TestResults {
+    statuses: Vec<TestResultStatus>,
+    /// The number of test cases that only passed after a retry.
+    num_flaky: usize,
+}
+
+impl TestResults {
+    fn add_event(&mut self, event: &Event) {
+        match *event {
+            Event::GlobalHookFinished { test_step, result, .. }
+            | Event::FeatureHookFinished { test_step, result, .. } => {
+                // Even a pending `BeforeAll` or `BeforeFeature` hook fails the run,
+                // because its cukes are skipped.
+                if skips_cukes(test_step) && result.status != TestResultStatus::Passed {
+                    self.statuses.push(TestResultStatus::Failed);
+                } else {
+                    self.statuses.push(result.status);
+                }
+            },
+            Event::TestCaseFinished { result, attempt, will_be_retried, .. } => {
+                if will_be_retried {
+                    return;
+                }
+                if attempt > 0 && result.status == TestResultStatus::Passed {
+                    self.num_flaky += 1;
+                }
+                self.statuses.push(result.status);
+            },
+            _ => {},
+        }
+    }
+
+    fn get_exit_status(&self, strict: bool) -> i32 {
+        if strict && self.num_flaky > 0 {
+            return 1;
+        }
+
+        self.statuses.iter()
+            .max()
+            .map(|status| if status.is_ok(strict) { 0 } else { 1 })
+            .unwrap_or(0)
+    }
+}
+
+/// Whether the cukes are skipped if the hook does not pass.
+fn skips_cukes(test_step: &TestStep) -> bool {
+    match test_step {
+        TestStep::Hook(hook) => match hook.get_hook_type() {
+            HookType::BeforeAll | HookType::BeforeFeature => true,
+            _ => false,
+        },
+        TestStep::Cuke(_) => false,
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct ExitStatusListener {
-    test_result_statuses: RefCell<Vec<TestResultStatus>>,
+    test_results: RefCell<TestResults>,
 }
 
 impl ExitStatusListener {
@@ -13,23 +69,22 @@ impl ExitStatusListener {
         ExitStatusListener::default()
     }
 
+    /// The exit status is 1 if a test case or hook is not ok, or (in strict mode)
+    /// if a test case is flaky.
     pub fn get_exit_status(&self, strict: bool) -> i32 {
-        let test_result_statuses = self.test_result_statuses.borrow();
-        get_exit_status(&test_result_statuses, strict)
+        self.test_results.borrow().get_exit_status(strict)
     }
 }
 
 impl EventListener for ExitStatusListener {
     fn on_event(&self, event: &Event) {
-        if let Event::TestCaseFinished { ref result, .. } = *event {
-            self.test_result_statuses.borrow_mut().push(result.status);
-        }
+        self.test_results.borrow_mut().add_event(event);
     }
 }
 
 #[derive(Debug, Default)]
 pub struct SyncExitStatusListener {
-    test_result_statuses: Mutex<RefCell<Vec<TestResultStatus>>>,
+    test_results: Mutex<RefCell<TestResults>>,
 }
 
 impl SyncExitStatusListener {
@@ -37,24 +92,17 @@ impl SyncExitStatusListener {
         SyncExitStatusListener::default()
     }
 
+    /// The exit status is 1 if a test case or hook is not ok, or (in strict mode)
+    /// if a test case is flaky.
     pub fn get_exit_status(&self, strict: bool) -> i32 {
-        let test_result_statuses_lock = self.test_result_statuses.lock().unwrap();
-        let test_result_statuses = test_result_statuses_lock.borrow();
-        get_exit_status(&test_result_statuses, strict)
+        let test_results_lock = self.test_results.lock().unwrap();
+        let test_results = test_results_lock.borrow();
+        test_results.get_exit_status(strict)
     }
 }
 
 impl EventListener for SyncExitStatusListener {
     fn on_event(&self, event: &Event) {
-        if let Event::TestCaseFinished { ref result, .. } = *event {
-            self.test_result_statuses.lock().unwrap().borrow_mut().push(result.status);
-        }
+        self.test_results.lock().unwrap().borrow_mut().add_event(event);
     }
 }
-
-fn get_exit_status(test_result_statuses: &[TestResultStatus], strict: bool) -> i32 {
-    test_result_statuses.iter()
-        .max()
-        .map(|status| if status.is_ok(strict) { 0 } else { 1 })
-        .unwrap_or(0)
-}
@@ -0,0 +1,512 @@
+use std::fmt::Write;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use base64;
+
+use gherkin::cuke;
+
+use api::{HookType, TestResult, TestResultStatus, TestStep};
+use api::event::{Event, EventListener};
+
+use super::report::{escape, seconds, take_finished_features, write_file, RunningTestCases};
+
+const HTML_FILE_NAME: &str = "cucumber.html";
+
+const STATUSES: [TestResultStatus; 6] = [
+    TestResultStatus::Passed,
+    TestResultStatus::Failed,
+    TestResultStatus::Ambiguous,
+    TestResultStatus::Undefined,
+    TestResultStatus::Pending,
+    TestResultStatus::Skipped,
+];
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.5em; }
+.controls { margin-bottom: 1.5em; }
+.controls label { margin-right: 1em; }
+.controls input[type=search] { width: 20em; margin-left: 1em; }
+.feature { margin-bottom: 1.5em; }
+.feature h2 { font-size: 1.2em; margin-bottom: 0.3em; }
+.uri, .duration, .location { color: #777; font-size: 0.85em; }
+.tags { color: #886; font-size: 0.85em; }
+.description { white-space: pre-wrap; color: #555; }
+details.scenario { border-left: 4px solid #ccc; margin: 0.4em 0; padding: 0.2em 0.6em; }
+details.scenario > summary { cursor: pointer; }
+ol.steps { list-style: none; padding-left: 1em; }
+.step { margin: 0.2em 0; }
+.keyword { font-weight: bold; }
+pre { background: #f6f6f6; padding: 0.5em; overflow-x: auto; }
+pre.error { background: #fdecea; }
+table.data-table { border-collapse: collapse; margin: 0.3em 0; }
+table.data-table td { border: 1px solid #ccc; padding: 0.1em 0.5em; }
+img.embedding { max-width: 100%; border: 1px solid #ccc; }
+.passed { border-color: #4caf50; } .status-passed { color: #2e7d32; }
+.failed { border-color: #e53935; } .status-failed { color: #c62828; }
+.ambiguous { border-color: #8e24aa; } .status-ambiguous { color: #6a1b9a; }
+.undefined { border-color: #fb8c00; } .status-undefined { color: #ef6c00; }
+.pending { border-color: #fdd835; } .status-pending { color: #9e8600; }
+.skipped { border-color: #29b6f6; } .status-skipped { color: #0277bd; }
+.hidden { display: none; }
+";
+
+const SCRIPT: &str = "
+function filterScenarios() {
+  var statuses = {};
+  document.querySelectorAll('.controls input[type=checkbox]').forEach(function (checkbox) {
+    statuses[checkbox.value] = checkbox.checked;
+  });
+  var search = document.getElementById('tag-search').value.trim().toLowerCase();
+  document.querySelectorAll('.feature').forEach(function (feature) {
+    var visible = 0;
+    feature.querySelectorAll('.scenario').forEach(function (scenario) {
+      var show = statuses[scenario.dataset.status]
+        && (search === '' || scenario.dataset.tags.toLowerCase().indexOf(search) >= 0);
+      scenario.classList.toggle('hidden', !show);
+      if (show) { visible++; }
+    });
+    feature.classList.toggle('hidden', visible === 0);
+  });
+}
+document.querySelectorAll('.controls input').forEach(function (input) {
+  input.addEventListener('input', filterScenarios);
+  input.addEventListener('change', filterScenarios);
+});
+";
+
+/// Writes a self-contained HTML report to the `cucumber.html` file of the output directory.
+///
+/// The report shows the features with their scenarios, which can be filtered by status
+/// and searched by tags. Failed scenarios are expanded, and embedded images and texts
+/// are shown inline. The styles and scripts are part of the file, so it works offline.
+///
+/// A retried scenario is shown once, with the steps and embeddings of its final attempt.
+#[derive(Debug)]
+pub struct HtmlFormatter {
+    path: PathBuf,
+    report: Mutex<HtmlReport>,
+}
+
+impl HtmlFormatter {
+    pub fn new(output_dir: &Path) -> HtmlFormatter {
+        HtmlFormatter {
+            path: output_dir.join(HTML_FILE_NAME),
+            report: Mutex::new(HtmlReport::default()),
+        }
+    }
+
+    fn write_html_file(&self) -> io::Result<()> {
+        let features = self.report.lock().unwrap().finish();
+        write_file(&self.path, to_html(&features))
+    }
+}
+
+impl EventListener for HtmlFormatter {
+    fn on_event(&self, event: &Event) {
+        match *event {
+            Event::TestSourceRead { uri, feature, .. } => {
+                self.report.lock().unwrap().features.push(HtmlFeature {
+                    uri: uri.to_owned(),
+                    keyword: feature.keyword.to_string(),
+                    name: feature.name.to_string(),
+                    description: feature.description.as_ref()
+                        .map(|description| description.to_string())
+                        .unwrap_or_default(),
+                    tags: feature.tags.iter()
+                        .map(|tag| tag.name.to_string())
+                        .collect(),
+                    scenarios: Vec::new(),
+                });
+            },
+            Event::TestCaseStarted { uri, scenario_definition, test_case, .. } => {
+                let html_scenario = HtmlScenario {
+                    keyword: scenario_definition.get_keyword().to_string(),
+                    name: test_case.get_name().to_owned(),
+                    line: test_case.get_line(),
+                    tags: test_case.get_tags().iter()
+                        .map(|tag| tag.name.to_owned())
+                        .collect(),
+                    status: TestResultStatus::Passed,
+                    duration: Duration::default(),
+                    steps: Vec::new(),
+                    embeddings: Vec::new(),
+                };
+                self.report.lock().unwrap().running_scenarios
+                    .start(uri, test_case.get_line(), html_scenario);
+            },
+            Event::TestStepFinished { uri, test_case, test_step, result, .. } => {
+                let mut report = self.report.lock().unwrap();
+                if let Some(html_scenario) = report.scenario_mut(uri, test_case.get_line()) {
+                    html_scenario.add_step_result(test_step, result);
+                }
+            },
+            Event::Embed { uri, line, data, ref mime_type, .. } => {
+                let mut report = self.report.lock().unwrap();
+                if let Some(html_scenario) = report.scenario_mut(uri, line) {
+                    html_scenario.embeddings.push(HtmlEmbedding {
+                        mime_type: mime_type.clone(),
+                        data: data.to_vec(),
+                    });
+                }
+            },
+            Event::Write { uri, line, text, .. } => {
+                let mut report = self.report.lock().unwrap();
+                if let Some(html_scenario) = report.scenario_mut(uri, line) {
+                    html_scenario.embeddings.push(HtmlEmbedding {
+                        mime_type: "text/plain".to_owned(),
+                        data: text.as_bytes().to_vec(),
+                    });
+                }
+            },
+            Event::TestCaseFinished { uri, test_case, result, will_be_retried, .. } => {
+                self.report.lock().unwrap()
+                    .finish_scenario(uri, test_case.get_line(), result, will_be_retried);
+            },
+            Event::TestRunFinished { .. } => {
+                if let Err(err) = self.write_html_file() {
+                    panic!("could not write HTML report \"{}\": {}", self.path.display(), err);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct HtmlReport {
+    /// In the order of the feature files.
+    features: Vec<HtmlFeature>,
+    running_scenarios: RunningTestCases<HtmlScenario>,
+}
+
+impl HtmlReport {
+    fn scenario_mut(&mut self, uri: &str, line: u32) -> Option<&mut HtmlScenario> {
+        self.running_scenarios.get_mut(uri, line)
+    }
+
+    fn finish_scenario(&mut self, uri: &str, line: u32, result: &TestResult, will_be_retried: bool) {
+        let mut html_scenario = match self.running_scenarios.finish(uri, line, will_be_retried) {
+            Some(html_scenario) => html_scenario,
+            None => return,
+        };
+
+        html_scenario.status = result.status;
+        html_scenario.duration = result.duration.unwrap_or_default();
+
+        if let Some(html_feature) = self.features.iter_mut().find(|html_feature| html_feature.uri == uri) {
+            html_feature.scenarios.push(html_scenario);
+        }
+    }
+
+    /// Returns the features with scenarios, which are shown in the order of the feature file.
+    fn finish(&mut self) -> Vec<HtmlFeature> {
+        take_finished_features(&mut self.features, |html_feature| &mut html_feature.scenarios,
+            |html_scenario| html_scenario.line)
+    }
+}
+
+#[derive(Debug)]
+struct HtmlFeature {
+    uri: String,
+    keyword: String,
+    name: String,
+    description: String,
+    tags: Vec<String>,
+    scenarios: Vec<HtmlScenario>,
+}
+
+#[derive(Debug)]
+struct HtmlScenario {
+    keyword: String,
+    name: String,
+    line: u32,
+    tags: Vec<String>,
+    status: TestResultStatus,
+    duration: Duration,
+    steps: Vec<HtmlStep>,
+    /// The embeddings of the next finished step or hook.
+    embeddings: Vec<HtmlEmbedding>,
+}
+
+impl HtmlScenario {
+    fn add_step_result(&mut self, test_step: &TestStep, result: &TestResult) {
+        let embeddings = mem::replace(&mut self.embeddings, Vec::new());
+
+        let html_step = match test_step {
+            TestStep::Hook(hook_test_step) => {
+                // passed hooks are only of interest when they embedded something
+                if result.status == TestResultStatus::Passed && embeddings.is_empty() {
+                    return;
+                }
+
+                HtmlStep {
+                    keyword: hook_keyword(hook_test_step.get_hook_type()).to_owned(),
+                    text: String::new(),
+                    location: hook_test_step.get_code_location().map(ToString::to_string),
+                    argument: None,
+                    status: result.status,
+                    duration: result.duration.unwrap_or_default(),
+                    error_message: result.get_error_message(),
+                    embeddings,
+                }
+            },
+            TestStep::Cuke(cuke_step_test_step) => HtmlStep {
+                keyword: cuke_step_test_step.get_step_keyword().to_owned(),
+                text: cuke_step_test_step.get_step_text().to_owned(),
+                location: cuke_step_test_step.get_code_location().map(ToString::to_string),
+                argument: cuke_step_test_step.get_cuke_step().argument.as_ref().map(|argument| match argument {
+                    cuke::Argument::String(string) => HtmlStepArgument::DocString(string.content.to_string()),
+                    cuke::Argument::Table(table) => HtmlStepArgument::DataTable(table.rows.iter()
+                        .map(|row| row.cells.iter()
+                            .map(|cell| AsRef::<str>::as_ref(cell).to_owned())
+                            .collect())
+                        .collect()),
+                }),
+                status: result.status,
+                duration: result.duration.unwrap_or_default(),
+                error_message: result.get_error_message(),
+                embeddings,
+            },
+        };
+
+        self.steps.push(html_step);
+    }
+}
+
+#[derive(Debug)]
+struct HtmlStep {
+    keyword: String,
+    text: String,
+    /// The code location of the glue.
+    location: Option<String>,
+    argument: Option<HtmlStepArgument>,
+    status: TestResultStatus,
+    duration: Duration,
+    error_message: Option<String>,
+    embeddings: Vec<HtmlEmbedding>,
+}
+
+#[derive(Debug)]
+enum HtmlStepArgument {
+    DocString(String),
+    DataTable(Vec<Vec<String>>),
+}
+
+#[derive(Debug)]
+struct HtmlEmbedding {
+    mime_type: String,
+    data: Vec<u8>,
+}
+
+fn hook_keyword(hook_type: HookType) -> &'static str {
+    match hook_type {
+        HookType::BeforeScenario => "Before",
+        HookType::AfterScenario => "After",
+        HookType::BeforeStep => "BeforeStep",
+        HookType::AfterStep => "AfterStep",
+        // global and feature hooks are not part of a test case
+        _ => "Hook",
+    }
+}
+
+fn status_class(status: TestResultStatus) -> String {
+    status.to_string().to_lowercase()
+}
+
+fn to_html(features: &[HtmlFeature]) -> String {
+    let mut html = String::new();
+
+    let scenarios = features.iter()
+        .flat_map(|html_feature| html_feature.scenarios.iter())
+        .collect::<Vec<&HtmlScenario>>();
+    let duration = scenarios.iter()
+        .map(|html_scenario| html_scenario.duration)
+        .fold(Duration::default(), |total, duration| total + duration);
+
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Cucumber report</title>\n");
+    writeln!(html, "<style>{}</style>", STYLE).unwrap();
+    html.push_str("</head>\n<body>\n");
+    writeln!(html, "<h1>Cucumber report</h1>\n<p>{} scenarios ({}) <span class=\"duration\">{}s</span></p>",
+        scenarios.len(), status_counts(&scenarios), seconds(duration)).unwrap();
+
+    html.push_str("<div class=\"controls\">\n");
+    for status in &STATUSES {
+        writeln!(html, "<label class=\"status-{}\"><input type=\"checkbox\" value=\"{}\" checked> {}</label>",
+            status_class(*status), status_class(*status), status).unwrap();
+    }
+    html.push_str("<input id=\"tag-search\" type=\"search\" placeholder=\"Search tags, e.g. @smoke\">\n");
+    html.push_str("</div>\n");
+
+    for html_feature in features {
+        write_feature(&mut html, html_feature);
+    }
+
+    writeln!(html, "<script>{}</script>", SCRIPT).unwrap();
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// The number of scenarios by status like "2 failed, 5 passed".
+fn status_counts(scenarios: &[&HtmlScenario]) -> String {
+    STATUSES.iter()
+        .map(|status| (status, scenarios.iter().filter(|html_scenario| html_scenario.status == *status).count()))
+        .filter(|&(_, count)| count > 0)
+        .map(|(status, count)| format!("<span class=\"status-{}\">{} {}</span>",
+            status_class(*status), count, status_class(*status)))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn write_feature(html: &mut String, html_feature: &HtmlFeature) {
+    html.push_str("<section class=\"feature\">\n");
+    write_tags(html, &html_feature.tags);
+    writeln!(html, "<h2><span class=\"keyword\">{}:</span> {} <span class=\"uri\">{}</span></h2>",
+        escape(&html_feature.keyword), escape(&html_feature.name), escape(&html_feature.uri)).unwrap();
+    if !html_feature.description.is_empty() {
+        writeln!(html, "<div class=\"description\">{}</div>", escape(&html_feature.description)).unwrap();
+    }
+
+    for html_scenario in &html_feature.scenarios {
+        write_scenario(html, &html_feature.uri, html_scenario);
+    }
+
+    html.push_str("</section>\n");
+}
+
+fn write_scenario(html: &mut String, uri: &str, html_scenario: &HtmlScenario) {
+    let status = status_class(html_scenario.status);
+    let open = if html_scenario.status == TestResultStatus::Passed { "" } else { " open" };
+
+    writeln!(html, "<details class=\"scenario {}\" data-status=\"{}\" data-tags=\"{}\"{}>",
+        status, status, escape(&html_scenario.tags.join(" ")), open).unwrap();
+    writeln!(html, "<summary><span class=\"status-{}\">{}</span> <span class=\"keyword\">{}:</span> {} \
+        <span class=\"location\">{}:{}</span> <span class=\"duration\">{}s</span></summary>",
+        status, html_scenario.status, escape(&html_scenario.keyword), escape(&html_scenario.name),
+        escape(uri), html_scenario.line, seconds(html_scenario.duration)).unwrap();
+    write_tags(html, &html_scenario.tags);
+
+    html.push_str("<ol class=\"steps\">\n");
+    for html_step in &html_scenario.steps {
+        write_step(html, html_step);
+    }
+    html.push_str("</ol>\n</details>\n");
+}
+
+fn write_step(html: &mut String, html_step: &HtmlStep) {
+    let status = status_class(html_step.status);
+
+    writeln!(html, "<li class=\"step\"><span class=\"status-{}\">{}</span> <span class=\"keyword\">{}</span> {} \
+        <span class=\"location\">{}</span> <span class=\"duration\">{}s</span>",
+        status, html_step.status, escape(html_step.keyword.trim()), escape(&html_step.text),
+        escape(html_step.location.as_ref().map(String::as_str).unwrap_or_default()),
+        seconds(html_step.duration)).unwrap();
+
+    match html_step.argument {
+        Some(HtmlStepArgument::DocString(ref doc_string)) => {
+            writeln!(html, "<pre>{}</pre>", escape(doc_string)).unwrap();
+        },
+        Some(HtmlStepArgument::DataTable(ref rows)) => {
+            html.push_str("<table class=\"data-table\">\n");
+            for row in rows {
+                html.push_str("<tr>");
+                for cell in row {
+                    write!(html, "<td>{}</td>", escape(cell)).unwrap();
+                }
+                html.push_str("</tr>\n");
+            }
+            html.push_str("</table>\n");
+        },
+        None => {},
+    }
+
+    if let Some(ref error_message) = html_step.error_message {
+        writeln!(html, "<pre class=\"error\">{}</pre>", escape(error_message)).unwrap();
+    }
+
+    for embedding in &html_step.embeddings {
+        write_embedding(html, embedding);
+    }
+
+    html.push_str("</li>\n");
+}
+
+/// Writes images and texts inline and other media as a download link.
+fn write_embedding(html: &mut String, embedding: &HtmlEmbedding) {
+    let mime_type = escape(&embedding.mime_type);
+
+    if embedding.mime_type.starts_with("image/") {
+        writeln!(html, "<img class=\"embedding\" src=\"data:{};base64,{}\">",
+            mime_type, base64::encode(&embedding.data)).unwrap();
+    } else if embedding.mime_type.starts_with("text/") {
+        writeln!(html, "<pre class=\"embedding\">{}</pre>",
+            escape(&String::from_utf8_lossy(&embedding.data))).unwrap();
+    } else {
+        writeln!(html, "<a class=\"embedding\" download href=\"data:{};base64,{}\">Embedded {}</a>",
+            mime_type, base64::encode(&embedding.data), mime_type).unwrap();
+    }
+}
+
+fn write_tags(html: &mut String, tags: &[String]) {
+    if !tags.is_empty() {
+        writeln!(html, "<div class=\"tags\">{}</div>", escape(&tags.join(" "))).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(status: TestResultStatus, error_message: Option<&str>, embeddings: Vec<HtmlEmbedding>) -> HtmlStep {
+        HtmlStep {
+            keyword: "Then ".to_owned(),
+            text: "the result is 3".to_owned(),
+            location: Some("tests/steps/calculator.rs:42".to_owned()),
+            argument: None,
+            status,
+            duration: Duration::from_millis(250),
+            error_message: error_message.map(str::to_owned),
+            embeddings,
+        }
+    }
+
+    #[test]
+    fn failed_scenarios_are_expanded_with_their_errors_and_embeddings() {
+        let features = vec![HtmlFeature {
+            uri: "tests/features/calculator.feature".to_owned(),
+            keyword: "Feature".to_owned(),
+            name: "Calculator".to_owned(),
+            description: String::new(),
+            tags: vec!["@calculator".to_owned()],
+            scenarios: vec![HtmlScenario {
+                keyword: "Scenario".to_owned(),
+                name: "Add <two> numbers".to_owned(),
+                line: 8,
+                tags: vec!["@calculator".to_owned(), "@smoke".to_owned()],
+                status: TestResultStatus::Failed,
+                duration: Duration::from_millis(500),
+                steps: vec![step(TestResultStatus::Failed, Some("expected 3, got 4"), vec![
+                    HtmlEmbedding { mime_type: "image/png".to_owned(), data: vec![1, 2, 3] },
+                    HtmlEmbedding { mime_type: "text/plain".to_owned(), data: b"1 + 2 = 4".to_vec() },
+                ])],
+                embeddings: Vec::new(),
+            }],
+        }];
+
+        let html = to_html(&features);
+
+        assert!(html.contains("<p>1 scenarios (<span class=\"status-failed\">1 failed</span>)"));
+        assert!(html.contains("<details class=\"scenario failed\" data-status=\"failed\" \
+            data-tags=\"@calculator @smoke\" open>"));
+        assert!(html.contains("Add &lt;two&gt; numbers"));
+        assert!(html.contains("<pre class=\"error\">expected 3, got 4</pre>"));
+        assert!(html.contains("<img class=\"embedding\" src=\"data:image/png;base64,AQID\">"));
+        assert!(html.contains("<pre class=\"embedding\">1 + 2 = 4</pre>"));
+        assert!(!html.contains("http"), "the report must not load external resources");
+    }
+}
@@ -0,0 +1,450 @@
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use base64;
+use serde_json;
+
+use gherkin::ast::{Background, Feature};
+use gherkin::cuke;
+
+use api::{CodeLocation, CukeStepTestStep, HookType, TestCase, TestResult, TestStep};
+use api::event::{Event, EventListener};
+
+use super::report::{duration_nanos, take_finished_features, write_file, RunningTestCases};
+
+const JSON_FILE_NAME: &str = "cucumber.json";
+
+/// Writes the test results in the cucumber JSON format to the `cucumber.json` file
+/// of the output directory (as used by the Jenkins cucumber-reports plugin, for example).
+///
+/// The elements of a retried test case are those of its final attempt.
+#[derive(Debug)]
+pub struct JsonFormatter {
+    path: PathBuf,
+    report: Mutex<JsonReport>,
+}
+
+impl JsonFormatter {
+    pub fn new(output_dir: &Path) -> JsonFormatter {
+        JsonFormatter {
+            path: output_dir.join(JSON_FILE_NAME),
+            report: Mutex::new(JsonReport::default()),
+        }
+    }
+
+    fn write_json_file(&self) -> io::Result<()> {
+        let features = self.report.lock().unwrap().finish();
+        write_file(&self.path, serde_json::to_string_pretty(&features)?)
+    }
+}
+
+impl EventListener for JsonFormatter {
+    fn on_event(&self, event: &Event) {
+        match *event {
+            Event::TestSourceRead { uri, feature, .. } => {
+                self.report.lock().unwrap().add_feature(uri, feature);
+            },
+            Event::TestCaseStarted { uri, feature, background, scenario_definition, test_case, .. } => {
+                self.report.lock().unwrap()
+                    .start_test_case(uri, feature, background, scenario_definition, test_case);
+            },
+            Event::TestStepFinished { uri, test_case, test_step, result, .. } => {
+                let mut report = self.report.lock().unwrap();
+                if let Some(json_test_case) = report.test_case_mut(uri, test_case.get_line()) {
+                    json_test_case.add_step_result(test_step, result);
+                }
+            },
+            Event::Embed { uri, line, data, ref mime_type, .. } => {
+                let mut report = self.report.lock().unwrap();
+                if let Some(json_test_case) = report.test_case_mut(uri, line) {
+                    json_test_case.embeddings.push(JsonEmbedding {
+                        mime_type: mime_type.clone(),
+                        data: base64::encode(data),
+                    });
+                }
+            },
+            Event::TestCaseFinished { uri, test_case, will_be_retried, .. } => {
+                self.report.lock().unwrap()
+                    .finish_test_case(uri, test_case.get_line(), will_be_retried);
+            },
+            Event::TestRunFinished { .. } => {
+                if let Err(err) = self.write_json_file() {
+                    panic!("could not write JSON report \"{}\": {}", self.path.display(), err);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct JsonReport {
+    /// In the order of the feature files.
+    features: Vec<JsonFeature>,
+    test_cases: RunningTestCases<JsonTestCase>,
+}
+
+impl JsonReport {
+    fn add_feature(&mut self, uri: &str, feature: &Feature) {
+        self.features.push(JsonFeature {
+            uri: uri.to_owned(),
+            id: json_id(&feature.name),
+            keyword: feature.keyword.to_string(),
+            name: feature.name.to_string(),
+            description: description(feature.description.as_ref()),
+            line: feature.location.line,
+            tags: feature.tags.iter()
+                .map(|tag| JsonTag {
+                    name: tag.name.to_string(),
+                    line: tag.location.line,
+                })
+                .collect(),
+            elements: Vec::new(),
+            test_cases: Vec::new(),
+        });
+    }
+
+    fn start_test_case(&mut self, uri: &str, feature: &Feature, background: Option<&Background>,
+        scenario_definition: &cuke::ScenarioDefinition, test_case: &TestCase)
+    {
+        let line = test_case.get_line();
+        let feature_id = json_id(&feature.name);
+        let scenario_id = match scenario_definition {
+            cuke::ScenarioDefinition::ScenarioOutline(_) => {
+                format!("{};{};{}", feature_id, json_id(test_case.get_name()), line)
+            },
+            _ => format!("{};{}", feature_id, json_id(test_case.get_name())),
+        };
+
+        let background = background.map(|background| JsonElement {
+            id: None,
+            keyword: background.keyword.to_string(),
+            element_type: "background",
+            name: background.name.to_string(),
+            description: description(background.description.as_ref()),
+            line: background.location.line,
+            tags: Vec::new(),
+            before: Vec::new(),
+            steps: Vec::new(),
+            after: Vec::new(),
+        });
+        let scenario = JsonElement {
+            id: Some(scenario_id),
+            keyword: scenario_definition.get_keyword().to_string(),
+            element_type: "scenario",
+            name: test_case.get_name().to_owned(),
+            description: description(scenario_definition.get_description()),
+            line,
+            tags: test_case.get_tags().iter()
+                .map(|tag| JsonTag {
+                    name: tag.as_ref().to_owned(),
+                    line: tag.location.line,
+                })
+                .collect(),
+            before: Vec::new(),
+            steps: Vec::new(),
+            after: Vec::new(),
+        };
+
+        self.test_cases.start(uri, line, JsonTestCase {
+            background,
+            scenario,
+            before_step_hooks: Vec::new(),
+            embeddings: Vec::new(),
+            last_step_is_background: false,
+        });
+    }
+
+    fn test_case_mut(&mut self, uri: &str, line: u32) -> Option<&mut JsonTestCase> {
+        self.test_cases.get_mut(uri, line)
+    }
+
+    fn finish_test_case(&mut self, uri: &str, line: u32, will_be_retried: bool) {
+        let json_test_case = match self.test_cases.finish(uri, line, will_be_retried) {
+            Some(json_test_case) => json_test_case,
+            None => return,
+        };
+
+        if let Some(json_feature) = self.features.iter_mut().find(|json_feature| json_feature.uri == uri) {
+            json_feature.test_cases.push((line, json_test_case.into_elements()));
+        }
+    }
+
+    /// Returns the features with test cases, with the elements of their test cases in line order.
+    fn finish(&mut self) -> Vec<JsonFeature> {
+        let mut features = take_finished_features(&mut self.features,
+            |json_feature| &mut json_feature.test_cases, |&(line, _)| line);
+
+        for json_feature in &mut features {
+            json_feature.elements = json_feature.test_cases.drain(..)
+                .flat_map(|(_, elements)| elements)
+                .collect();
+        }
+
+        features
+    }
+}
+
+#[derive(Debug)]
+struct JsonTestCase {
+    background: Option<JsonElement>,
+    scenario: JsonElement,
+    /// The `BeforeStep` hooks of the next step.
+    before_step_hooks: Vec<JsonHook>,
+    /// The embeddings of the next finished step or hook.
+    embeddings: Vec<JsonEmbedding>,
+    last_step_is_background: bool,
+}
+
+impl JsonTestCase {
+    fn add_step_result(&mut self, test_step: &TestStep, result: &TestResult) {
+        match test_step {
+            TestStep::Hook(hook_test_step) => {
+                let json_hook = JsonHook {
+                    hook_match: JsonMatch::new(hook_test_step.get_code_location()),
+                    result: JsonResult::new(result),
+                    embeddings: mem::replace(&mut self.embeddings, Vec::new()),
+                };
+
+                match hook_test_step.get_hook_type() {
+                    HookType::BeforeScenario => self.scenario.before.push(json_hook),
+                    HookType::AfterScenario => self.scenario.after.push(json_hook),
+                    HookType::BeforeStep => self.before_step_hooks.push(json_hook),
+                    HookType::AfterStep => {
+                        if let Some(json_step) = self.last_step_mut() {
+                            json_step.after.push(json_hook);
+                        }
+                    },
+                    // global and feature hooks are not part of a test case
+                    _ => {},
+                }
+            },
+            TestStep::Cuke(cuke_step_test_step) => {
+                let before = mem::replace(&mut self.before_step_hooks, Vec::new());
+                let embeddings = mem::replace(&mut self.embeddings, Vec::new());
+                let json_step = JsonStep::new(*cuke_step_test_step, result, before, embeddings);
+
+                self.last_step_is_background = cuke_step_test_step.is_background_step()
+                    && self.background.is_some();
+                match self.background {
+                    Some(ref mut background) if self.last_step_is_background => background.steps.push(json_step),
+                    _ => self.scenario.steps.push(json_step),
+                }
+            },
+        }
+    }
+
+    fn last_step_mut(&mut self) -> Option<&mut JsonStep> {
+        match self.background {
+            Some(ref mut background) if self.last_step_is_background => background.steps.last_mut(),
+            _ => self.scenario.steps.last_mut(),
+        }
+    }
+
+    fn into_elements(self) -> Vec<JsonElement> {
+        match self.background {
+            Some(background) => vec![background, self.scenario],
+            None => vec![self.scenario],
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct JsonFeature {
+    uri: String,
+    id: String,
+    keyword: String,
+    name: String,
+    description: String,
+    line: u32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<JsonTag>,
+    elements: Vec<JsonElement>,
+    /// The elements of the finished test cases by line.
+    #[serde(skip)]
+    test_cases: Vec<(u32, Vec<JsonElement>)>,
+}
+
+/// A background or scenario.
+#[derive(Debug, Serialize)]
+struct JsonElement {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    keyword: String,
+    #[serde(rename = "type")]
+    element_type: &'static str,
+    name: String,
+    description: String,
+    line: u32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<JsonTag>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    before: Vec<JsonHook>,
+    steps: Vec<JsonStep>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    after: Vec<JsonHook>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonStep {
+    keyword: String,
+    name: String,
+    line: u32,
+    #[serde(rename = "match")]
+    step_match: JsonMatch,
+    result: JsonResult,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    doc_string: Option<JsonDocString>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    rows: Vec<JsonRow>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    embeddings: Vec<JsonEmbedding>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    before: Vec<JsonHook>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    after: Vec<JsonHook>,
+}
+
+impl JsonStep {
+    fn new(cuke_step_test_step: &CukeStepTestStep, result: &TestResult, before: Vec<JsonHook>,
+        embeddings: Vec<JsonEmbedding>) -> JsonStep
+    {
+        let mut doc_string = None;
+        let mut rows = Vec::new();
+
+        match cuke_step_test_step.get_cuke_step().argument.as_ref() {
+            Some(cuke::Argument::String(string)) => doc_string = Some(JsonDocString {
+                value: string.content.to_string(),
+            }),
+            Some(cuke::Argument::Table(table)) => rows = table.rows.iter()
+                .map(|row| JsonRow {
+                    cells: row.cells.iter()
+                        .map(|cell| AsRef::<str>::as_ref(cell).to_owned())
+                        .collect(),
+                })
+                .collect(),
+            None => {},
+        }
+
+        JsonStep {
+            keyword: cuke_step_test_step.get_step_keyword().to_owned(),
+            name: cuke_step_test_step.get_step_text().to_owned(),
+            line: cuke_step_test_step.get_step_line(),
+            step_match: JsonMatch::new(cuke_step_test_step.get_code_location()),
+            result: JsonResult::new(result),
+            doc_string,
+            rows,
+            embeddings,
+            before,
+            after: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct JsonHook {
+    #[serde(rename = "match")]
+    hook_match: JsonMatch,
+    result: JsonResult,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    embeddings: Vec<JsonEmbedding>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonMatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+}
+
+impl JsonMatch {
+    fn new(code_location: Option<&CodeLocation>) -> JsonMatch {
+        JsonMatch {
+            location: code_location.map(ToString::to_string),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct JsonResult {
+    status: String,
+    /// In nanoseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_message: Option<String>,
+}
+
+impl JsonResult {
+    fn new(result: &TestResult) -> JsonResult {
+        JsonResult {
+            status: result.status.to_string().to_lowercase(),
+            duration: result.duration.map(duration_nanos),
+            error_message: result.get_error_message(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct JsonTag {
+    name: String,
+    line: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonDocString {
+    value: String,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonRow {
+    cells: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonEmbedding {
+    mime_type: String,
+    /// Base64 encoded.
+    data: String,
+}
+
+/// The id of a feature or scenario like `rpn-calculator` for "RPN calculator".
+fn json_id(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_whitespace() || "'_,!".contains(c) { '-' } else { c })
+        .collect()
+}
+
+fn description<S: AsRef<str>>(description: Option<S>) -> String {
+    description.map(|description| description.as_ref().to_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_are_lowercase_with_dashes() {
+        assert_eq!(json_id("RPN calculator"), "rpn-calculator");
+        assert_eq!(json_id("Don't divide by zero, ever!"), "don-t-divide-by-zero--ever-");
+    }
+
+    #[test]
+    fn empty_lists_are_not_serialized() {
+        let json_hook = JsonHook {
+            hook_match: JsonMatch { location: Some("tests/steps/mod.rs:12".to_owned()) },
+            result: JsonResult {
+                status: "passed".to_owned(),
+                duration: Some(1500),
+                error_message: None,
+            },
+            embeddings: Vec::new(),
+        };
+
+        assert_eq!(serde_json::to_string(&json_hook).unwrap(),
+            r#"{"match":{"location":"tests/steps/mod.rs:12"},"result":{"status":"passed","duration":1500}}"#);
+    }
+}
@@ -0,0 +1,290 @@
+use std::fmt::Write;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use gherkin::cuke;
+
+use api::{TestCase, TestResult, TestResultStatus};
+use api::event::{Event, EventListener};
+
+use super::report::{escape, seconds, take_finished_features, write_file, RunningTestCases};
+
+const JUNIT_FILE_NAME: &str = "junit.xml";
+
+/// Writes the test results as JUnit XML to the `junit.xml` file of the output directory,
+/// with one `<testsuite>` per feature and one `<testcase>` per cuke.
+///
+/// A retried cuke is a single `<testcase>` with the result and output of its final attempt.
+#[derive(Debug)]
+pub struct JUnitFormatter {
+    path: PathBuf,
+    report: Mutex<JUnitReport>,
+}
+
+impl JUnitFormatter {
+    pub fn new(output_dir: &Path) -> JUnitFormatter {
+        JUnitFormatter {
+            path: output_dir.join(JUNIT_FILE_NAME),
+            report: Mutex::new(JUnitReport::default()),
+        }
+    }
+
+    fn write_junit_file(&self) -> io::Result<()> {
+        let test_suites = self.report.lock().unwrap().finish();
+        write_file(&self.path, to_xml(&test_suites))
+    }
+}
+
+impl EventListener for JUnitFormatter {
+    fn on_event(&self, event: &Event) {
+        match *event {
+            Event::TestSourceRead { uri, feature, .. } => {
+                self.report.lock().unwrap().test_suites.push(JUnitTestSuite {
+                    uri: uri.to_owned(),
+                    name: feature.name.to_string(),
+                    test_cases: Vec::new(),
+                });
+            },
+            Event::TestCaseStarted { uri, feature, scenario_definition, test_case, .. } => {
+                let junit_test_case = JUnitTestCase {
+                    class_name: feature.name.to_string(),
+                    name: test_case_name(scenario_definition, test_case),
+                    status: TestResultStatus::Passed,
+                    duration: Duration::default(),
+                    error_message: None,
+                    system_out: String::new(),
+                };
+                self.report.lock().unwrap().running_test_cases
+                    .start(uri, test_case.get_line(), junit_test_case);
+            },
+            Event::Write { uri, line, text, .. } => {
+                let mut report = self.report.lock().unwrap();
+                if let Some(junit_test_case) = report.running_test_cases.get_mut(uri, line) {
+                    junit_test_case.system_out.push_str(text);
+                    junit_test_case.system_out.push('\n');
+                }
+            },
+            Event::TestCaseFinished { uri, test_case, result, will_be_retried, .. } => {
+                self.report.lock().unwrap()
+                    .finish_test_case(uri, test_case.get_line(), result, will_be_retried);
+            },
+            Event::TestRunFinished { .. } => {
+                if let Err(err) = self.write_junit_file() {
+                    panic!("could not write JUnit report \"{}\": {}", self.path.display(), err);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct JUnitReport {
+    /// In the order of the feature files.
+    test_suites: Vec<JUnitTestSuite>,
+    running_test_cases: RunningTestCases<JUnitTestCase>,
+}
+
+impl JUnitReport {
+    fn finish_test_case(&mut self, uri: &str, line: u32, result: &TestResult, will_be_retried: bool) {
+        let mut junit_test_case = match self.running_test_cases.finish(uri, line, will_be_retried) {
+            Some(junit_test_case) => junit_test_case,
+            None => return,
+        };
+
+        junit_test_case.status = result.status;
+        junit_test_case.duration = result.duration.unwrap_or_default();
+        junit_test_case.error_message = result.get_error_message();
+
+        if let Some(test_suite) = self.test_suites.iter_mut().find(|test_suite| test_suite.uri == uri) {
+            test_suite.test_cases.push((line, junit_test_case));
+        }
+    }
+
+    /// Returns the test suites with test cases, so a feature without cukes to run
+    /// is not reported as an empty `<testsuite>`.
+    fn finish(&mut self) -> Vec<JUnitTestSuite> {
+        take_finished_features(&mut self.test_suites, |test_suite| &mut test_suite.test_cases,
+            |&(line, _)| line)
+    }
+}
+
+#[derive(Debug)]
+struct JUnitTestSuite {
+    uri: String,
+    name: String,
+    /// The finished test cases by line.
+    test_cases: Vec<(u32, JUnitTestCase)>,
+}
+
+impl JUnitTestSuite {
+    fn count(&self, predicate: fn(TestResultStatus) -> bool) -> usize {
+        self.test_cases.iter()
+            .filter(|(_, test_case)| predicate(test_case.status))
+            .count()
+    }
+
+    fn duration(&self) -> Duration {
+        self.test_cases.iter()
+            .map(|(_, test_case)| test_case.duration)
+            .fold(Duration::default(), |total, duration| total + duration)
+    }
+}
+
+#[derive(Debug)]
+struct JUnitTestCase {
+    class_name: String,
+    name: String,
+    status: TestResultStatus,
+    duration: Duration,
+    error_message: Option<String>,
+    /// The text of the `Write` events.
+    system_out: String,
+}
+
+fn is_failure(status: TestResultStatus) -> bool {
+    match status {
+        TestResultStatus::Failed | TestResultStatus::Ambiguous => true,
+        _ => false,
+    }
+}
+
+fn is_skipped(status: TestResultStatus) -> bool {
+    match status {
+        TestResultStatus::Skipped | TestResultStatus::Pending | TestResultStatus::Undefined => true,
+        _ => false,
+    }
+}
+
+/// The name of the test case, where the example rows of a scenario outline get their position
+/// (like "Add two numbers (example #1.3)" for the third row of the first examples table),
+/// so the names are unique and do not change with the selected cukes.
+fn test_case_name(scenario_definition: &cuke::ScenarioDefinition, test_case: &TestCase) -> String {
+    let scenario_outline = match scenario_definition {
+        cuke::ScenarioDefinition::ScenarioOutline(scenario_outline) => scenario_outline,
+        _ => return test_case.get_name().to_owned(),
+    };
+
+    let line = test_case.get_line();
+    for (examples_index, examples) in scenario_outline.examples.iter().enumerate() {
+        let table_body = match examples.table_body {
+            Some(ref table_body) => table_body,
+            None => continue,
+        };
+
+        if let Some(row_index) = table_body.iter().position(|table_row| table_row.location.line == line) {
+            return format!("{} (example #{}.{})", test_case.get_name(), examples_index + 1, row_index + 1);
+        }
+    }
+
+    format!("{} (line {})", test_case.get_name(), line)
+}
+
+fn to_xml(test_suites: &[JUnitTestSuite]) -> String {
+    let mut xml = String::new();
+
+    let tests = test_suites.iter().map(|test_suite| test_suite.test_cases.len()).sum::<usize>();
+    let failures = test_suites.iter().map(|test_suite| test_suite.count(is_failure)).sum::<usize>();
+    let skipped = test_suites.iter().map(|test_suite| test_suite.count(is_skipped)).sum::<usize>();
+    let duration = test_suites.iter()
+        .map(JUnitTestSuite::duration)
+        .fold(Duration::default(), |total, duration| total + duration);
+
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(xml, "<testsuites name=\"cucumber\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{}\">",
+        tests, failures, skipped, seconds(duration)).unwrap();
+
+    for test_suite in test_suites {
+        writeln!(xml, "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{}\">",
+            escape(&test_suite.name), test_suite.test_cases.len(), test_suite.count(is_failure),
+            test_suite.count(is_skipped), seconds(test_suite.duration())).unwrap();
+
+        for (_, test_case) in &test_suite.test_cases {
+            write_test_case(&mut xml, test_case);
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn write_test_case(xml: &mut String, test_case: &JUnitTestCase) {
+    write!(xml, "    <testcase classname=\"{}\" name=\"{}\" time=\"{}\"",
+        escape(&test_case.class_name), escape(&test_case.name), seconds(test_case.duration)).unwrap();
+
+    if test_case.status == TestResultStatus::Passed && test_case.system_out.is_empty() {
+        xml.push_str("/>\n");
+        return;
+    }
+    xml.push_str(">\n");
+
+    let error_message = test_case.error_message.as_ref().map(String::as_str).unwrap_or_default();
+    let message = error_message.lines().next().unwrap_or_default();
+    if is_failure(test_case.status) {
+        writeln!(xml, "      <failure message=\"{}\" type=\"{}\">{}</failure>",
+            escape(message), test_case.status, escape(error_message)).unwrap();
+    } else if is_skipped(test_case.status) {
+        let status = test_case.status.to_string();
+        let message = if message.is_empty() { &status } else { message };
+        writeln!(xml, "      <skipped message=\"{}\"/>", escape(message)).unwrap();
+    }
+
+    if !test_case.system_out.is_empty() {
+        writeln!(xml, "      <system-out>{}</system-out>", escape(&test_case.system_out)).unwrap();
+    }
+
+    xml.push_str("    </testcase>\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_case(name: &str, status: TestResultStatus, error_message: Option<&str>, system_out: &str)
+        -> JUnitTestCase
+    {
+        JUnitTestCase {
+            class_name: "Calculator".to_owned(),
+            name: name.to_owned(),
+            status,
+            duration: Duration::from_millis(1500),
+            error_message: error_message.map(str::to_owned),
+            system_out: system_out.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_suites_contain_failures_skipped_and_system_out() {
+        let test_suites = vec![JUnitTestSuite {
+            uri: "tests/features/calculator.feature".to_owned(),
+            name: "Calculator".to_owned(),
+            test_cases: vec![
+                (3, test_case("Add", TestResultStatus::Passed, None, "")),
+                (8, test_case("Divide", TestResultStatus::Failed, Some("division by zero\nat step 2"), "1 / 0\n")),
+                (12, test_case("Square root", TestResultStatus::Undefined, None, "")),
+            ],
+        }];
+
+        assert_eq!(to_xml(&test_suites), "\
+<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<testsuites name=\"cucumber\" tests=\"3\" failures=\"1\" skipped=\"1\" time=\"4.500\">
+  <testsuite name=\"Calculator\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\" time=\"4.500\">
+    <testcase classname=\"Calculator\" name=\"Add\" time=\"1.500\"/>
+    <testcase classname=\"Calculator\" name=\"Divide\" time=\"1.500\">
+      <failure message=\"division by zero\" type=\"Failed\">division by zero
+at step 2</failure>
+      <system-out>1 / 0
+</system-out>
+    </testcase>
+    <testcase classname=\"Calculator\" name=\"Square root\" time=\"1.500\">
+      <skipped message=\"Undefined\"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+");
+    }
+}
@@ -1,9 +1,24 @@
 pub(crate) use self::exit_status::*;
 pub(crate) use self::test_summary::*;
+pub use self::html_formatter::*;
+pub use self::json_formatter::*;
+pub use self::junit_formatter::*;
+pub use self::ndjson_formatter::*;
 pub use self::pretty_formatter::*;
 pub use self::progress_bar::*;
+pub use self::rerun_formatter::*;
+pub use self::tap_formatter::*;
+pub use self::usage_formatter::*;
 
 mod exit_status;
 mod test_summary;
+mod html_formatter;
+mod json_formatter;
+mod junit_formatter;
+mod ndjson_formatter;
 mod pretty_formatter;
 mod progress_bar;
+mod report;
+mod rerun_formatter;
+mod tap_formatter;
+mod usage_formatter;
@@ -0,0 +1,603 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64;
+use serde_json::{self, Value};
+
+use gherkin::ast::{Feature, TableRow, Tag};
+use gherkin::cuke::{self, Cuke};
+
+use api::{CodeLocation, HookDefinitionInfo, StepDefinitionInfo, TestCase, TestResult, TestStep};
+use api::event::{Event, EventListener};
+
+use super::report::{create_file, RunningTestCases};
+
+const NDJSON_FILE_NAME: &str = "cucumber.ndjson";
+
+/// Writes the Cucumber Messages protocol as newline delimited JSON to the `cucumber.ndjson` file
+/// of the output directory (as read by the official HTML reporter, for example).
+///
+/// The ids linking the messages are derived from the feature file locations and the code
+/// locations of the glue, so they are stable between runs:
+///
+/// ```text ignore
+/// | Message            | Id                                               |
+/// |--------------------|--------------------------------------------------|
+/// | stepDefinition     | tests/steps/calculator.rs:12                     |
+/// | gherkinDocument    | tests/features/calculator.feature:8 (AST nodes)  |
+/// | pickle             | pickle:tests/features/calculator.feature:8       |
+/// | testCase           | test-case:tests/features/calculator.feature:8    |
+/// | testCaseStarted    | test-case:tests/features/calculator.feature:8#0  |
+/// ```
+///
+/// A `testCase` message is written when its test case is started for the first time,
+/// and every attempt of a retried test case gets its own `testCaseStarted` message.
+/// Global and feature hooks are not part of a test case and are not reported.
+#[derive(Debug)]
+pub struct NdjsonFormatter {
+    path: PathBuf,
+    strict: bool,
+    state: Mutex<NdjsonState>,
+}
+
+impl NdjsonFormatter {
+    pub fn new(output_dir: &Path, strict: bool) -> NdjsonFormatter {
+        NdjsonFormatter {
+            path: output_dir.join(NDJSON_FILE_NAME),
+            strict,
+            state: Mutex::new(NdjsonState::default()),
+        }
+    }
+
+    fn write_messages(&self, state: &mut NdjsonState, messages: &[Value]) {
+        if let Err(err) = state.write_messages(&self.path, messages) {
+            panic!("could not write NDJSON messages \"{}\": {}", self.path.display(), err);
+        }
+    }
+}
+
+impl EventListener for NdjsonFormatter {
+    fn on_event(&self, event: &Event) {
+        let mut state = self.state.lock().unwrap();
+
+        match *event {
+            Event::GlueLoaded { step_definitions, hook_definitions, .. } => {
+                let messages = step_definitions.iter()
+                    .map(step_definition_message)
+                    .chain(hook_definitions.iter().map(hook_message))
+                    .collect::<Vec<Value>>();
+                self.write_messages(&mut state, &messages);
+            },
+            Event::TestSourceRead { uri, source, feature, cukes, .. } => {
+                let mut messages = vec![
+                    json!({ "source": {
+                        "uri": uri,
+                        "data": source,
+                        "mediaType": "text/x.cucumber.gherkin+plain",
+                    }}),
+                    json!({ "gherkinDocument": {
+                        "uri": uri,
+                        "feature": feature_node(uri, feature, cukes),
+                    }}),
+                ];
+                messages.extend(cukes.iter().map(|cuke| pickle_message(uri, cuke)));
+                self.write_messages(&mut state, &messages);
+            },
+            Event::TestRunStarted { time, .. } => {
+                self.write_messages(&mut state, &[json!({ "testRunStarted": {
+                    "timestamp": timestamp(time),
+                }})]);
+            },
+            Event::TestCaseStarted { time, uri, test_case, attempt, .. } => {
+                let line = test_case.get_line();
+                let test_case_id = id("test-case", uri, line);
+                let test_case_started_id = format!("{}#{}", test_case_id, attempt);
+                let test_step_ids = (0..test_case.get_test_steps().len())
+                    .map(|index| format!("{};{}", id("test-step", uri, line), index))
+                    .collect::<Vec<String>>();
+
+                let mut messages = Vec::with_capacity(2);
+                if attempt == 0 {
+                    messages.push(test_case_message(uri, test_case, &test_step_ids));
+                }
+                messages.push(json!({ "testCaseStarted": {
+                    "id": test_case_started_id,
+                    "testCaseId": test_case_id,
+                    "attempt": attempt,
+                    "timestamp": timestamp(time),
+                }}));
+                self.write_messages(&mut state, &messages);
+
+                state.test_cases.start(uri, line, NdjsonTestCase {
+                    test_case_started_id,
+                    test_step_ids,
+                    next_test_step: 0,
+                });
+            },
+            Event::TestStepStarted { time, uri, test_case, .. } => {
+                let message = match state.test_cases.get(uri, test_case.get_line()) {
+                    Some(ndjson_test_case) => json!({ "testStepStarted": {
+                        "testCaseStartedId": ndjson_test_case.test_case_started_id,
+                        "testStepId": ndjson_test_case.current_test_step_id(),
+                        "timestamp": timestamp(time),
+                    }}),
+                    None => return,
+                };
+                self.write_messages(&mut state, &[message]);
+            },
+            Event::TestStepFinished { time, uri, test_case, result, .. } => {
+                let message = match state.test_cases.get_mut(uri, test_case.get_line()) {
+                    Some(ndjson_test_case) => {
+                        let message = json!({ "testStepFinished": {
+                            "testCaseStartedId": ndjson_test_case.test_case_started_id,
+                            "testStepId": ndjson_test_case.current_test_step_id(),
+                            "testStepResult": test_step_result(result),
+                            "timestamp": timestamp(time),
+                        }});
+                        ndjson_test_case.next_test_step += 1;
+                        message
+                    },
+                    None => return,
+                };
+                self.write_messages(&mut state, &[message]);
+            },
+            Event::Embed { uri, line, data, ref mime_type, .. } => {
+                let message = match state.test_cases.get(uri, line) {
+                    Some(ndjson_test_case) => attachment_message(ndjson_test_case,
+                        &base64::encode(data), "BASE64", mime_type),
+                    None => return,
+                };
+                self.write_messages(&mut state, &[message]);
+            },
+            Event::Write { uri, line, text, .. } => {
+                let message = match state.test_cases.get(uri, line) {
+                    Some(ndjson_test_case) => attachment_message(ndjson_test_case,
+                        text, "IDENTITY", "text/x.cucumber.log+plain"),
+                    None => return,
+                };
+                self.write_messages(&mut state, &[message]);
+            },
+            Event::TestCaseFinished { time, uri, test_case, result, will_be_retried, .. } => {
+                let ndjson_test_case = match state.test_cases.remove(uri, test_case.get_line()) {
+                    Some(ndjson_test_case) => ndjson_test_case,
+                    None => return,
+                };
+                if !will_be_retried && !result.status.is_ok(self.strict) {
+                    state.failed = true;
+                }
+
+                self.write_messages(&mut state, &[json!({ "testCaseFinished": {
+                    "testCaseStartedId": ndjson_test_case.test_case_started_id,
+                    "timestamp": timestamp(time),
+                    "willBeRetried": will_be_retried,
+                }})]);
+            },
+            Event::TestRunFinished { time } => {
+                let success = !state.failed;
+                self.write_messages(&mut state, &[json!({ "testRunFinished": {
+                    "timestamp": timestamp(time),
+                    "success": success,
+                }})]);
+
+                if let Err(err) = state.flush() {
+                    panic!("could not write NDJSON messages \"{}\": {}", self.path.display(), err);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct NdjsonState {
+    /// Created with the first message.
+    writer: Option<BufWriter<File>>,
+    test_cases: RunningTestCases<NdjsonTestCase>,
+    /// Whether the final attempt of a test case did not pass.
+    failed: bool,
+}
+
+impl NdjsonState {
+    fn write_messages(&mut self, path: &Path, messages: &[Value]) -> io::Result<()> {
+        if self.writer.is_none() {
+            self.writer = Some(BufWriter::new(create_file(path)?));
+        }
+
+        let writer = self.writer.as_mut().unwrap();
+        for message in messages {
+            serde_json::to_writer(&mut *writer, message)?;
+            writer.write_all(b"\n")?;
+        }
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.writer {
+            Some(ref mut writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct NdjsonTestCase {
+    test_case_started_id: String,
+    /// In the order of `TestCase::get_test_steps`, which is the execution order.
+    test_step_ids: Vec<String>,
+    next_test_step: usize,
+}
+
+impl NdjsonTestCase {
+    fn current_test_step_id(&self) -> Option<&str> {
+        self.test_step_ids.get(self.next_test_step).map(String::as_str)
+    }
+}
+
+/// An id like `pickle:tests/features/calculator.feature:8`.
+fn id(kind: &str, uri: &str, line: u32) -> String {
+    format!("{}:{}", kind, ast_node_id(uri, line))
+}
+
+/// The id of a scenario, background, step, examples or table row of a feature file.
+fn ast_node_id(uri: &str, line: u32) -> String {
+    format!("{}:{}", uri, line)
+}
+
+fn tag_id(uri: &str, line: u32, column: u32) -> String {
+    format!("{}:{}", ast_node_id(uri, line), column)
+}
+
+fn pickle_step_id(uri: &str, pickle_line: u32, step_line: u32) -> String {
+    format!("{};{}", id("pickle-step", uri, pickle_line), step_line)
+}
+
+fn step_line(step: &cuke::Step) -> u32 {
+    step.locations.last()
+        .map(|location| location.line)
+        .unwrap_or(0)
+}
+
+fn step_definition_message(step_definition: &StepDefinitionInfo) -> Value {
+    json!({ "stepDefinition": {
+        "id": step_definition.location.to_string(),
+        "pattern": {
+            "source": step_definition.expression,
+            "type": if step_definition.is_regex { "REGULAR_EXPRESSION" } else { "CUCUMBER_EXPRESSION" },
+        },
+        "sourceReference": source_reference(step_definition.location),
+    }})
+}
+
+fn hook_message(hook_definition: &HookDefinitionInfo) -> Value {
+    let mut hook = json!({
+        "id": hook_definition.location.to_string(),
+        "sourceReference": source_reference(hook_definition.location),
+    });
+    if !hook_definition.tag_expression.is_empty() {
+        hook["tagExpression"] = json!(hook_definition.tag_expression);
+    }
+
+    json!({ "hook": hook })
+}
+
+fn source_reference(code_location: &CodeLocation) -> Value {
+    json!({
+        "uri": code_location.file_path,
+        "location": { "line": code_location.line_number },
+    })
+}
+
+/// The feature of a `gherkinDocument` message, whose background and scenarios
+/// are taken from the cukes that were compiled from it.
+fn feature_node(uri: &str, feature: &Feature, cukes: &[Cuke]) -> Value {
+    let mut children = Vec::new();
+
+    if let Some((cuke, background)) = cukes.iter()
+        .find_map(|cuke| cuke.background.map(|background| (cuke, background)))
+    {
+        children.push(json!({ "background": {
+            "id": ast_node_id(uri, background.location.line),
+            "location": location(background.location.line, background.location.column),
+            "keyword": background.keyword.to_string(),
+            "name": background.name.to_string(),
+            "description": description(background.description.as_ref()),
+            "steps": cuke.background_steps.iter()
+                .map(|step| step_node(uri, step))
+                .collect::<Vec<Value>>(),
+        }}));
+    }
+
+    // The cukes of a scenario outline share their scenario node.
+    let mut scenarios = BTreeMap::new();
+    for cuke in cukes {
+        let line = cuke.locations.last()
+            .map(|location| location.line)
+            .unwrap_or(0);
+        scenarios.entry(line).or_insert_with(|| scenario_node(uri, feature, cuke, line));
+    }
+    children.extend(scenarios.into_iter().map(|(_, scenario)| json!({ "scenario": scenario })));
+
+    json!({
+        "location": location(feature.location.line, feature.location.column),
+        "tags": tag_nodes(uri, &feature.tags),
+        "language": feature.language.to_string(),
+        "keyword": feature.keyword.to_string(),
+        "name": feature.name.to_string(),
+        "description": description(feature.description.as_ref()),
+        "children": children,
+    })
+}
+
+fn scenario_node(uri: &str, feature: &Feature, cuke: &Cuke, line: u32) -> Value {
+    match &cuke.scenario_definition {
+        cuke::ScenarioDefinition::ScenarioOutline(scenario_outline) => json!({
+            "id": ast_node_id(uri, line),
+            "location": location(line, scenario_outline.location.column),
+            "tags": tag_nodes(uri, &scenario_outline.tags),
+            "keyword": cuke.scenario_definition.get_keyword().to_string(),
+            "name": scenario_outline.name.to_string(),
+            "description": description(cuke.scenario_definition.get_description()),
+            "steps": scenario_outline.steps.iter()
+                .map(|step| json!({
+                    "id": ast_node_id(uri, step.location.line),
+                    "location": location(step.location.line, step.location.column),
+                    "keyword": step.keyword.to_string(),
+                    "text": step.text.to_string(),
+                }))
+                .collect::<Vec<Value>>(),
+            "examples": scenario_outline.examples.iter()
+                .map(|examples| json!({
+                    "id": ast_node_id(uri, examples.location.line),
+                    "location": location(examples.location.line, examples.location.column),
+                    "tags": tag_nodes(uri, &examples.tags),
+                    "keyword": examples.keyword.to_string(),
+                    "name": examples.name.to_string(),
+                    "description": description(examples.description.as_ref()),
+                    "tableHeader": examples.table_header.as_ref()
+                        .map(|table_row| table_row_node(uri, table_row)),
+                    "tableBody": examples.table_body.iter()
+                        .flat_map(|table_body| table_body.iter())
+                        .map(|table_row| table_row_node(uri, table_row))
+                        .collect::<Vec<Value>>(),
+                }))
+                .collect::<Vec<Value>>(),
+        }),
+        _ => {
+            // The cuke also has the tags of the feature, which are written above the feature line.
+            let scenario_tags = cuke.tags.iter()
+                .filter(|tag| tag.location.line > feature.location.line)
+                .map(|tag| tag_node(uri, tag.name, tag.location.line, tag.location.column))
+                .collect::<Vec<Value>>();
+
+            json!({
+                "id": ast_node_id(uri, line),
+                "location": location(line, cuke.locations.last().map_or(0, |location| location.column)),
+                "tags": scenario_tags,
+                "keyword": cuke.scenario_definition.get_keyword().to_string(),
+                "name": cuke.name.to_string(),
+                "description": description(cuke.scenario_definition.get_description()),
+                "steps": cuke.scenario_steps.iter()
+                    .map(|step| step_node(uri, step))
+                    .collect::<Vec<Value>>(),
+                "examples": [],
+            })
+        },
+    }
+}
+
+fn step_node(uri: &str, step: &cuke::Step) -> Value {
+    let line = step_line(step);
+    let column = step.locations.last().map_or(0, |location| location.column);
+
+    let mut step_node = json!({
+        "id": ast_node_id(uri, line),
+        "location": location(line, column),
+        "keyword": step.keyword.to_string(),
+        "text": step.text.to_string(),
+    });
+    match step.argument.as_ref() {
+        Some(cuke::Argument::String(string)) => step_node["docString"] = doc_string(string),
+        Some(cuke::Argument::Table(table)) => step_node["dataTable"] = data_table(table),
+        None => {},
+    }
+
+    step_node
+}
+
+fn table_row_node(uri: &str, table_row: &TableRow) -> Value {
+    json!({
+        "id": ast_node_id(uri, table_row.location.line),
+        "location": location(table_row.location.line, table_row.location.column),
+        "cells": table_row.cells.iter()
+            .map(|cell| json!({
+                "location": location(cell.location.line, cell.location.column),
+                "value": cell.value.to_string(),
+            }))
+            .collect::<Vec<Value>>(),
+    })
+}
+
+fn tag_nodes(uri: &str, tags: &[Tag]) -> Vec<Value> {
+    tags.iter()
+        .map(|tag| tag_node(uri, tag.name.as_ref(), tag.location.line, tag.location.column))
+        .collect()
+}
+
+fn tag_node(uri: &str, name: &str, line: u32, column: u32) -> Value {
+    json!({
+        "id": tag_id(uri, line, column),
+        "location": location(line, column),
+        "name": name,
+    })
+}
+
+fn location(line: u32, column: u32) -> Value {
+    json!({ "line": line, "column": column })
+}
+
+fn doc_string(string: &cuke::String) -> Value {
+    json!({ "content": string.content.to_string() })
+}
+
+fn data_table(table: &cuke::Table) -> Value {
+    json!({ "rows": table.rows.iter()
+        .map(|row| json!({ "cells": row.cells.iter()
+            .map(|cell| json!({ "value": AsRef::<str>::as_ref(cell) }))
+            .collect::<Vec<Value>>(),
+        }))
+        .collect::<Vec<Value>>(),
+    })
+}
+
+fn pickle_message(uri: &str, cuke: &Cuke) -> Value {
+    let pickle_line = cuke.locations[0].line;
+    let ast_node_ids = cuke.locations.iter().rev()
+        .map(|location| ast_node_id(uri, location.line))
+        .collect::<Vec<String>>();
+
+    json!({ "pickle": {
+        "id": id("pickle", uri, pickle_line),
+        "uri": uri,
+        "name": cuke.name.to_string(),
+        "astNodeIds": ast_node_ids,
+        "tags": cuke.tags.iter()
+            .map(|tag| json!({
+                "name": tag.name,
+                "astNodeId": tag_id(uri, tag.location.line, tag.location.column),
+            }))
+            .collect::<Vec<Value>>(),
+        "steps": cuke.background_steps.iter()
+            .chain(cuke.scenario_steps.iter())
+            .map(|step| pickle_step(uri, pickle_line, step))
+            .collect::<Vec<Value>>(),
+    }})
+}
+
+fn pickle_step(uri: &str, pickle_line: u32, step: &cuke::Step) -> Value {
+    let mut pickle_step = json!({
+        "id": pickle_step_id(uri, pickle_line, step_line(step)),
+        "text": step.text.to_string(),
+        // The step of a scenario outline is followed by the examples row.
+        "astNodeIds": step.locations.iter().rev()
+            .map(|location| ast_node_id(uri, location.line))
+            .collect::<Vec<String>>(),
+    });
+    match step.argument.as_ref() {
+        Some(cuke::Argument::String(string)) => pickle_step["argument"] = json!({ "docString": doc_string(string) }),
+        Some(cuke::Argument::Table(table)) => pickle_step["argument"] = json!({ "dataTable": data_table(table) }),
+        None => {},
+    }
+
+    pickle_step
+}
+
+fn test_case_message(uri: &str, test_case: &TestCase, test_step_ids: &[String]) -> Value {
+    let line = test_case.get_line();
+    let test_steps = test_case.get_test_steps().iter()
+        .zip(test_step_ids)
+        .map(|(test_step, test_step_id)| match test_step {
+            TestStep::Hook(hook_test_step) => json!({
+                "id": test_step_id,
+                "hookId": hook_test_step.get_code_location().map(ToString::to_string),
+            }),
+            TestStep::Cuke(cuke_step_test_step) => json!({
+                "id": test_step_id,
+                "pickleStepId": pickle_step_id(uri, line, cuke_step_test_step.get_step_line()),
+                "stepDefinitionIds": cuke_step_test_step.get_code_location()
+                    .map(ToString::to_string)
+                    .into_iter()
+                    .collect::<Vec<String>>(),
+            }),
+        })
+        .collect::<Vec<Value>>();
+
+    json!({ "testCase": {
+        "id": id("test-case", uri, line),
+        "pickleId": id("pickle", uri, line),
+        "testSteps": test_steps,
+    }})
+}
+
+fn test_step_result(result: &TestResult) -> Value {
+    let mut test_step_result = json!({
+        "status": result.status.to_string().to_uppercase(),
+        "duration": duration(result.duration.unwrap_or_default()),
+    });
+    if let Some(message) = result.get_error_message() {
+        test_step_result["message"] = json!(message);
+    }
+
+    test_step_result
+}
+
+fn attachment_message(ndjson_test_case: &NdjsonTestCase, body: &str, content_encoding: &str,
+    media_type: &str) -> Value
+{
+    json!({ "attachment": {
+        "testCaseStartedId": ndjson_test_case.test_case_started_id,
+        "testStepId": ndjson_test_case.current_test_step_id(),
+        "body": body,
+        "contentEncoding": content_encoding,
+        "mediaType": media_type,
+    }})
+}
+
+fn timestamp(time: SystemTime) -> Value {
+    duration(time.duration_since(UNIX_EPOCH).unwrap_or_default())
+}
+
+fn duration(duration: Duration) -> Value {
+    json!({ "seconds": duration.as_secs(), "nanos": duration.subsec_nanos() })
+}
+
+fn description<S: AsRef<str>>(description: Option<S>) -> String {
+    description.map(|description| description.as_ref().to_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use api::HookType;
+
+    const LOCATION: CodeLocation = CodeLocation {
+        file_path: "tests/steps/calculator.rs",
+        line_number: 12,
+    };
+
+    #[test]
+    fn durations_are_split_into_seconds_and_nanos() {
+        assert_eq!(duration(Duration::new(2, 5)), json!({ "seconds": 2, "nanos": 5 }));
+    }
+
+    #[test]
+    fn glue_ids_are_code_locations() {
+        let step_definition = StepDefinitionInfo {
+            expression: "I add {int} and {int}",
+            is_regex: false,
+            location: &LOCATION,
+        };
+        assert_eq!(step_definition_message(&step_definition), json!({ "stepDefinition": {
+            "id": "tests/steps/calculator.rs:12",
+            "pattern": { "source": "I add {int} and {int}", "type": "CUCUMBER_EXPRESSION" },
+            "sourceReference": { "uri": "tests/steps/calculator.rs", "location": { "line": 12 } },
+        }}));
+
+        let hook_definition = HookDefinitionInfo {
+            hook_type: HookType::BeforeScenario,
+            tag_expression: "@calculator",
+            location: &LOCATION,
+        };
+        assert_eq!(hook_message(&hook_definition), json!({ "hook": {
+            "id": "tests/steps/calculator.rs:12",
+            "sourceReference": { "uri": "tests/steps/calculator.rs", "location": { "line": 12 } },
+            "tagExpression": "@calculator",
+        }}));
+    }
+}
@@ -5,7 +5,8 @@ use std::cell::RefCell;
 use gherkin::ast::{Feature, Background, ScenarioOutline, Examples, Tag};
 use gherkin::cuke;
 
-use api::{CodeLocation, TestCase, TestResult, TestStep, CukeStepTestStep};
+use api::{CodeLocation, TestCase, TestResult, TestResultStatus, TestStep, CukeStepTestStep};
+use api::HookTestStep;
 use api::event::{Event, EventListener};
 use glue::step::argument::StepArgument;
 
@@ -59,6 +60,12 @@ impl EventListener for PrettyFormatter {
                 test_case,
                 ..
             } => self.inner.borrow_mut().handle_test_case_started(uri, feature, background, scenario_definition, test_case),
+            Event::TestCaseFinished {
+                result,
+                attempt,
+                will_be_retried,
+                ..
+            } => self.inner.borrow().handle_test_case_finished(result, attempt, will_be_retried),
             Event::TestStepStarted {
                 uri,
                 scenario_definition,
@@ -75,6 +82,16 @@ impl EventListener for PrettyFormatter {
                 text,
                 ..
             } => self.inner.borrow().handle_write(text),
+            Event::GlobalHookFinished {
+                test_step,
+                result,
+                ..
+            } => self.inner.borrow_mut().handle_global_hook_finished(test_step, result),
+            Event::FeatureHookFinished {
+                test_step,
+                result,
+                ..
+            } => self.inner.borrow_mut().handle_global_hook_finished(test_step, result),
             _ => {},
         }
     }
@@ -96,6 +113,13 @@ impl Inner {
         }
     }
 
+    fn handle_test_case_finished(&self, result: &TestResult, attempt: u32, will_be_retried: bool) {
+        if will_be_retried {
+            println!("{}\x1B[{}mAttempt {} failed, retrying\x1B[0m", SCENARIO_INDENT,
+                result.status.ansi_color_code(), attempt + 1);
+        }
+    }
+
     fn print_background(&mut self, uri: &str, background: &Background, test_case: &TestCase) {
         let definition_text = format!("{}: {}", background.keyword, background.name);
         let background_line = background.location.line;
@@ -122,12 +146,30 @@ impl Inner {
     }
 
     fn handle_test_step_finished(&mut self, test_step: &TestStep, result: &TestResult) {
-        if let TestStep::Cuke(cuke_step_test_step) = test_step {
-            self.print_step(*cuke_step_test_step, result);
+        match test_step {
+            TestStep::Cuke(cuke_step_test_step) => self.print_step(*cuke_step_test_step, result),
+            // passed hooks are not part of the feature file, so only the others are shown
+            TestStep::Hook(hook_test_step) => match result.status {
+                TestResultStatus::Passed | TestResultStatus::Skipped => {},
+                _ => self.print_hook(*hook_test_step, result),
+            },
         }
         self.print_error(result);
     }
 
+    /// Prints a non-passed global or feature hook, which does not belong to any test case.
+    fn handle_global_hook_finished(&mut self, test_step: &TestStep, result: &TestResult) {
+        if let TestStep::Hook(hook_test_step) = test_step {
+            if result.status != TestResultStatus::Passed {
+                let hook_text = self.hook_text(*hook_test_step);
+                self.location_indentation = STEP_INDENT.chars().count() + hook_text.chars().count() + 1;
+                println!();
+                self.print_hook(*hook_test_step, result);
+                self.print_error(result);
+            }
+        }
+    }
+
     fn handle_write(&self, text: &str) {
         println!("{}", text);
     }
@@ -142,6 +184,14 @@ impl Inner {
         println!("{}", STEP_INDENT.to_owned() + &formatted_step_text + &location_padding + &location);
     }
 
+    fn print_hook(&self, test_step: &HookTestStep, result: &TestResult) {
+        let hook_text = self.hook_text(test_step);
+        let location_padding = self.create_padding_to_location(STEP_INDENT, &hook_text);
+        let formatted_hook_text = format!("\x1B[{}m{}\x1B[0m", result.status.ansi_color_code(), hook_text);
+        let location = self.format_code_location(test_step.get_code_location());
+        println!("{}", STEP_INDENT.to_owned() + &formatted_hook_text + &location_padding + &location);
+    }
+
     fn format_step_text(&self, keyword: &str, step_text: &str,
         ansi_color_code: u8, arguments: &[StepArgument]) -> String
     {
@@ -343,6 +393,10 @@ impl Inner {
         self.location_indentation = max_text_length;
     }
 
+    fn hook_text(&self, test_step: &HookTestStep) -> String {
+        format!("{:?} hook (order {})", test_step.get_hook_type(), test_step.get_order())
+    }
+
     fn step_text(&self, test_step: &CukeStepTestStep) -> String {
         let keyword = test_step.get_step_keyword();
         let text = test_step.get_step_text();
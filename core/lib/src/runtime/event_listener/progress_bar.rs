@@ -32,8 +32,10 @@ impl EventListener for ProgressBarListener {
                 self.progress_bar.enable_steady_tick(100);
                 self.progress_bar.set_draw_target(ProgressDrawTarget::stderr())
             },
-            Event::TestCaseFinished { .. } => {
-                self.progress_bar.inc(1);
+            Event::TestCaseFinished { will_be_retried, .. } => {
+                if !will_be_retried {
+                    self.progress_bar.inc(1);
+                }
             },
             Event::TestRunFinished { .. } => {
                 self.progress_bar.finish();
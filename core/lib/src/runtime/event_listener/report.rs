@@ -0,0 +1,143 @@
+//! The helpers of the formatters that write report files to the output directory.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// Creates the file and the output directory it is in.
+pub fn create_file(path: &Path) -> io::Result<File> {
+    if let Some(output_dir) = path.parent() {
+        fs::create_dir_all(output_dir)?;
+    }
+    File::create(path)
+}
+
+pub fn write_file<C: AsRef<[u8]>>(path: &Path, contents: C) -> io::Result<()> {
+    create_file(path)?.write_all(contents.as_ref())
+}
+
+/// The test cases that were started but not finished yet, by uri and line.
+#[derive(Debug)]
+pub struct RunningTestCases<T> {
+    test_cases: HashMap<(String, u32), T>,
+}
+
+impl<T> Default for RunningTestCases<T> {
+    fn default() -> Self {
+        RunningTestCases {
+            test_cases: HashMap::new(),
+        }
+    }
+}
+
+impl<T> RunningTestCases<T> {
+    /// Starts a test case, replacing the previous attempt if the test case is retried.
+    pub fn start(&mut self, uri: &str, line: u32, test_case: T) {
+        self.test_cases.insert((uri.to_owned(), line), test_case);
+    }
+
+    pub fn get(&self, uri: &str, line: u32) -> Option<&T> {
+        self.test_cases.get(&(uri.to_owned(), line))
+    }
+
+    pub fn get_mut(&mut self, uri: &str, line: u32) -> Option<&mut T> {
+        self.test_cases.get_mut(&(uri.to_owned(), line))
+    }
+
+    pub fn remove(&mut self, uri: &str, line: u32) -> Option<T> {
+        self.test_cases.remove(&(uri.to_owned(), line))
+    }
+
+    /// Removes the finished test case, returning it only if it is the final attempt
+    /// that gets reported.
+    pub fn finish(&mut self, uri: &str, line: u32, will_be_retried: bool) -> Option<T> {
+        self.remove(uri, line).filter(|_| !will_be_retried)
+    }
+}
+
+/// Takes the features of the report that have finished test cases, with their test cases
+/// sorted by line (the test cases of the parallel execution modes finish in any order).
+pub fn take_finished_features<F, T, C, L>(features: &mut Vec<F>, test_cases: C, line: L) -> Vec<F>
+    where C: Fn(&mut F) -> &mut Vec<T>, L: Fn(&T) -> u32
+{
+    let mut finished_features = Vec::new();
+
+    for mut feature in features.drain(..) {
+        if test_cases(&mut feature).is_empty() {
+            continue;
+        }
+        test_cases(&mut feature).sort_by_key(|test_case| line(test_case));
+        finished_features.push(feature);
+    }
+
+    finished_features
+}
+
+pub fn duration_nanos(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000_000 + u64::from(duration.subsec_nanos())
+}
+
+/// The duration in seconds with millisecond precision like `1.500`.
+pub fn seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1e9)
+}
+
+/// Escapes the text for XML and HTML content and attribute values,
+/// dropping the control characters that are not allowed in XML.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '\n' | '\r' | '\t' => escaped.push(c),
+            c if c.is_control() => {},
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn special_characters_are_escaped() {
+        assert_eq!(escape("a < b && \"c\"\u{1b}"), "a &lt; b &amp;&amp; &quot;c&quot;");
+    }
+
+    #[test]
+    fn durations_are_in_nanoseconds_or_seconds() {
+        assert_eq!(duration_nanos(Duration::new(2, 5)), 2_000_000_005);
+        assert_eq!(seconds(Duration::from_millis(1500)), "1.500");
+    }
+
+    #[test]
+    fn only_final_attempts_are_finished() {
+        let mut running_test_cases = RunningTestCases::default();
+
+        running_test_cases.start("calculator.feature", 8, "first attempt");
+        assert_eq!(running_test_cases.finish("calculator.feature", 8, true), None);
+        running_test_cases.start("calculator.feature", 8, "second attempt");
+        assert_eq!(running_test_cases.finish("calculator.feature", 8, false), Some("second attempt"));
+    }
+
+    #[test]
+    fn finished_features_have_test_cases_sorted_by_line() {
+        let mut features = vec![vec![12, 8], Vec::new(), vec![3]];
+
+        let finished_features = take_finished_features(&mut features, |test_cases| test_cases,
+            |&line| line);
+
+        assert_eq!(finished_features, vec![vec![8, 12], vec![3]]);
+        assert!(features.is_empty());
+    }
+}
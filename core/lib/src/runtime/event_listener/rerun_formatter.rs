@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use api::event::{Event, EventListener};
+use runtime::filter::FeatureLocation;
+
+use super::report::write_file;
+
+const RERUN_FILE_NAME: &str = "rerun.txt";
+
+/// Writes the feature locations (like `tests/features/calculator.feature:12:30`) of the test cases
+/// that are not ok to the `rerun.txt` file of the output directory.
+///
+/// The file can be passed as `Config::rerun_file` to only run these test cases again.
+#[derive(Debug)]
+pub struct RerunFormatter {
+    path: PathBuf,
+    strict: bool,
+    lines_by_uri: Mutex<BTreeMap<String, Vec<u32>>>,
+}
+
+impl RerunFormatter {
+    pub fn new(output_dir: &Path, strict: bool) -> RerunFormatter {
+        RerunFormatter {
+            path: output_dir.join(RERUN_FILE_NAME),
+            strict,
+            lines_by_uri: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Writes one feature location per feature file, sorted by uri and line,
+    /// so the file is the same for every execution mode.
+    fn write_rerun_file(&self) -> io::Result<()> {
+        let lines_by_uri = self.lines_by_uri.lock().unwrap();
+
+        let mut rerun_file = String::new();
+        for (uri, lines) in lines_by_uri.iter() {
+            let mut lines = lines.clone();
+            lines.sort();
+            rerun_file.push_str(&FeatureLocation::new(PathBuf::from(uri), lines).to_string());
+            rerun_file.push('\n');
+        }
+
+        write_file(&self.path, rerun_file)
+    }
+}
+
+impl EventListener for RerunFormatter {
+    fn on_event(&self, event: &Event) {
+        match *event {
+            Event::TestCaseFinished { test_case, result, will_be_retried, .. } => {
+                if !will_be_retried && !result.status.is_ok(self.strict) {
+                    self.lines_by_uri.lock().unwrap()
+                        .entry(test_case.get_uri().to_owned())
+                        .or_insert_with(Vec::new)
+                        .push(test_case.get_line());
+                }
+            },
+            Event::TestRunFinished { .. } => {
+                if let Err(err) = self.write_rerun_file() {
+                    panic!("could not write rerun file \"{}\": {}", self.path.display(), err);
+                }
+            },
+            _ => {},
+        }
+    }
+}
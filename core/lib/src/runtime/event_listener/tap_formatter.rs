@@ -0,0 +1,147 @@
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde_json;
+
+use api::TestResultStatus;
+use api::event::{Event, EventListener};
+
+/// Prints the test results in the Test Anything Protocol (TAP version 13) to stdout,
+/// with one test line per cuke and the failures in YAML diagnostic blocks.
+/// The test summary is printed to stderr instead, so it is not mixed into the TAP stream.
+///
+/// The tests are numbered in the order they finish, so the numbering stays consistent
+/// in the parallel execution modes. A retried cuke gets a single test line for its final attempt.
+#[derive(Debug)]
+pub struct TapFormatter {
+    state: Mutex<TapState>,
+}
+
+#[derive(Debug, Default)]
+struct TapState {
+    /// The number of cukes of the plan.
+    num_cukes: usize,
+    /// The number of the last printed test line.
+    last_number: usize,
+}
+
+impl TapFormatter {
+    pub fn new() -> TapFormatter {
+        TapFormatter {
+            state: Mutex::new(TapState::default()),
+        }
+    }
+}
+
+impl EventListener for TapFormatter {
+    fn on_event(&self, event: &Event) {
+        match *event {
+            Event::TestRunStarted { num_cukes, .. } => {
+                self.state.lock().unwrap().num_cukes = num_cukes;
+                println!("TAP version 13");
+                println!("1..{}", num_cukes);
+            },
+            Event::TestCaseFinished { test_case, result, will_be_retried, .. } => {
+                if will_be_retried {
+                    return;
+                }
+
+                // The test line is printed while holding the lock, so the numbers are in order.
+                let mut state = self.state.lock().unwrap();
+                state.last_number += 1;
+                print!("{}", test_line(state.last_number, &test_case.get_scenario_designation(),
+                    result.status, result.duration.unwrap_or_default(),
+                    result.get_error_message().as_ref().map(String::as_str)));
+            },
+            Event::TestRunFinished { .. } => {
+                let state = self.state.lock().unwrap();
+                if state.last_number < state.num_cukes {
+                    println!("Bail out! {} of {} cukes were not run", state.num_cukes - state.last_number,
+                        state.num_cukes);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// The test line of a test case, followed by a YAML diagnostic block if it did not pass.
+fn test_line(number: usize, designation: &str, status: TestResultStatus, duration: Duration,
+    error_message: Option<&str>) -> String
+{
+    let (ok, directive) = match status {
+        TestResultStatus::Passed => ("ok", ""),
+        TestResultStatus::Skipped => ("ok", " # SKIP"),
+        TestResultStatus::Pending => ("not ok", " # TODO pending"),
+        TestResultStatus::Undefined => ("not ok", " # TODO undefined step"),
+        TestResultStatus::Ambiguous | TestResultStatus::Failed => ("not ok", ""),
+    };
+
+    let mut line = String::new();
+    writeln!(line, "{} {} - {}{}", ok, number, escape_description(designation), directive).unwrap();
+
+    if let TestResultStatus::Ambiguous | TestResultStatus::Failed = status {
+        let error_message = error_message.unwrap_or_default();
+
+        line.push_str("  ---\n");
+        // a JSON string is a valid YAML string, unlike the `Debug` format with escapes like `\u{1b}`
+        let message = serde_json::to_string(error_message.lines().next().unwrap_or_default()).unwrap();
+        writeln!(line, "  message: {}", message).unwrap();
+        writeln!(line, "  severity: {}", status.to_string().to_lowercase()).unwrap();
+        writeln!(line, "  duration_ms: {}", duration.as_secs() * 1000 + u64::from(duration.subsec_millis())).unwrap();
+        if !error_message.is_empty() {
+            line.push_str("  stack: |\n");
+            for error_line in error_message.lines() {
+                writeln!(line, "    {}", error_line).unwrap();
+            }
+        }
+        line.push_str("  ...\n");
+    }
+
+    line
+}
+
+/// Escapes the `#` of a test description, which would otherwise start a directive.
+fn escape_description(description: &str) -> String {
+    description.replace('\\', "\\\\").replace('#', "\\#")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skipped_and_pending_test_cases_have_directives() {
+        assert_eq!(test_line(1, "tests/features/calculator.feature:8 # Add", TestResultStatus::Passed,
+            Duration::from_millis(5), None), "ok 1 - tests/features/calculator.feature:8 \\# Add\n");
+        assert_eq!(test_line(2, "a.feature:3 # Divide", TestResultStatus::Skipped, Duration::default(), None),
+            "ok 2 - a.feature:3 \\# Divide # SKIP\n");
+        assert_eq!(test_line(3, "a.feature:9 # Sum", TestResultStatus::Pending, Duration::default(), None),
+            "not ok 3 - a.feature:9 \\# Sum # TODO pending\n");
+    }
+
+    #[test]
+    fn failures_have_a_yaml_diagnostic_block() {
+        assert_eq!(test_line(4, "a.feature:12 # Divide by zero", TestResultStatus::Failed,
+            Duration::from_millis(1250), Some("division by \"zero\"\nat step 2")), "\
+not ok 4 - a.feature:12 \\# Divide by zero
+  ---
+  message: \"division by \\\"zero\\\"\"
+  severity: failed
+  duration_ms: 1250
+  stack: |
+    division by \"zero\"
+    at step 2
+  ...
+");
+    }
+
+    #[test]
+    fn diagnostic_messages_are_valid_yaml_strings() {
+        let line = test_line(5, "a.feature:20 # Colors", TestResultStatus::Failed, Duration::default(),
+            Some("expected \u{1b}[32mgreen"));
+
+        assert!(line.contains("  message: \"expected \\u001b[32mgreen\"\n"), "{}", line);
+    }
+}
@@ -6,7 +6,7 @@ use std::cell::RefCell;
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
 use api::event::{Event, EventListener};
-use api::TestResultStatus;
+use api::{TestCase, TestResult, TestResultStatus, TestStep};
 
 #[derive(Debug, Default)]
 struct StatusSummary {
@@ -17,6 +17,12 @@ struct StatusSummary {
     undefined: u32,
     ambiguous: u32,
     failed: u32,
+    flaky: u32,
+    filtered_out: usize,
+    ambiguous_steps: Vec<String>,
+    failed_hooks: Vec<String>,
+    flaky_scenarios: Vec<String>,
+    snippets: Vec<String>,
 }
 
 impl StatusSummary {
@@ -32,6 +38,61 @@ impl StatusSummary {
             TestResultStatus::Failed => self.failed += 1,
         }
     }
+
+    /// Records the final attempt of a test case, which is flaky if it only passed after a retry.
+    fn add_test_case_result(&mut self, test_case: &TestCase, result: &TestResult, attempt: u32,
+        will_be_retried: bool)
+    {
+        if will_be_retried {
+            return;
+        }
+
+        self.add_status(result.status);
+
+        if attempt > 0 && result.status == TestResultStatus::Passed {
+            self.flaky += 1;
+            self.flaky_scenarios.push(format!("{} (passed after {} attempts)",
+                test_case.get_scenario_designation(), attempt + 1));
+        }
+    }
+
+    fn add_step_result(&mut self, test_step: &TestStep, result: &TestResult) {
+        if result.status != TestResultStatus::Ambiguous {
+            return;
+        }
+
+        if let TestStep::Cuke(cuke_step_test_step) = test_step {
+            let error_message = result.get_error_message().unwrap_or_default();
+            self.ambiguous_steps.push(format!("{}: {}",
+                cuke_step_test_step.get_step_location(), error_message));
+        }
+    }
+
+    /// Records a failed global hook, or a failed feature hook together with the feature `uri`.
+    fn add_hook_result(&mut self, uri: Option<&str>, test_step: &TestStep, result: &TestResult) {
+        if result.status == TestResultStatus::Passed {
+            return;
+        }
+
+        if let TestStep::Hook(hook_test_step) = test_step {
+            let location = hook_test_step.get_code_location()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            let feature = uri.map(|uri| format!(" (feature {})", uri))
+                .unwrap_or_default();
+            let error_message = result.get_error_message().unwrap_or_default();
+            self.failed_hooks.push(format!("{:?} hook {}{}: {}",
+                hook_test_step.get_hook_type(), location, feature, error_message));
+        }
+    }
+
+    fn add_snippets(&mut self, snippets: &[String]) {
+        for snippet in snippets {
+            if !self.snippets.contains(snippet) {
+                self.snippets.push(snippet.clone());
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -48,16 +109,40 @@ impl TestSummaryListener {
         }
     }
 
-    pub fn print_test_summary(&self) {
+    /// Prints the summary to stdout, or to stderr if stdout is reserved for a formatter like TAP.
+    pub fn print_test_summary(&self, to_stderr: bool) {
         let summary = self.status_summary.borrow();
-        print_test_summary(&summary, self.start_time);
+        print_test_summary(&summary, self.start_time, to_stderr);
+    }
+
+    #[cfg(test)]
+    pub fn get_flaky_scenarios(&self) -> Vec<String> {
+        self.status_summary.borrow().flaky_scenarios.clone()
     }
 }
 
 impl EventListener for TestSummaryListener {
     fn on_event(&self, event: &Event) {
-        if let Event::TestCaseFinished { ref result, .. } = *event {
-            self.status_summary.borrow_mut().add_status(result.status)
+        match *event {
+            Event::TestRunStarted { num_filtered_out, .. } => {
+                self.status_summary.borrow_mut().filtered_out = num_filtered_out
+            },
+            Event::TestStepFinished { test_step, result, .. } => {
+                self.status_summary.borrow_mut().add_step_result(test_step, result)
+            },
+            Event::GlobalHookFinished { test_step, result, .. } => {
+                self.status_summary.borrow_mut().add_hook_result(None, test_step, result)
+            },
+            Event::FeatureHookFinished { uri, test_step, result, .. } => {
+                self.status_summary.borrow_mut().add_hook_result(Some(uri), test_step, result)
+            },
+            Event::SnippetsSuggested { snippets, .. } => {
+                self.status_summary.borrow_mut().add_snippets(snippets)
+            },
+            Event::TestCaseFinished { test_case, result, attempt, will_be_retried, .. } => {
+                self.status_summary.borrow_mut().add_test_case_result(test_case, result, attempt, will_be_retried)
+            },
+            _ => {},
         }
     }
 }
@@ -76,53 +161,117 @@ impl SyncTestSummaryListener {
         }
     }
 
-    pub fn print_test_summary(&self) {
+    /// Prints the summary to stdout, or to stderr if stdout is reserved for a formatter like TAP.
+    pub fn print_test_summary(&self, to_stderr: bool) {
         let status_summary_lock = self.status_summary.lock().unwrap();
         let summary = status_summary_lock.borrow();
-        print_test_summary(&summary, self.start_time);
+        print_test_summary(&summary, self.start_time, to_stderr);
     }
 }
 
 impl EventListener for SyncTestSummaryListener {
     fn on_event(&self, event: &Event) {
-        if let Event::TestCaseFinished { ref result, .. } = *event {
-            self.status_summary.lock().unwrap().borrow_mut().add_status(result.status)
+        match *event {
+            Event::TestRunStarted { num_filtered_out, .. } => {
+                self.status_summary.lock().unwrap().borrow_mut().filtered_out = num_filtered_out
+            },
+            Event::TestStepFinished { test_step, result, .. } => {
+                self.status_summary.lock().unwrap().borrow_mut().add_step_result(test_step, result)
+            },
+            Event::GlobalHookFinished { test_step, result, .. } => {
+                self.status_summary.lock().unwrap().borrow_mut().add_hook_result(None, test_step, result)
+            },
+            Event::FeatureHookFinished { uri, test_step, result, .. } => {
+                self.status_summary.lock().unwrap().borrow_mut().add_hook_result(Some(uri), test_step, result)
+            },
+            Event::SnippetsSuggested { snippets, .. } => {
+                self.status_summary.lock().unwrap().borrow_mut().add_snippets(snippets)
+            },
+            Event::TestCaseFinished { test_case, result, attempt, will_be_retried, .. } => {
+                self.status_summary.lock().unwrap().borrow_mut()
+                    .add_test_case_result(test_case, result, attempt, will_be_retried)
+            },
+            _ => {},
         }
     }
 }
 
-fn print_test_summary(summary: &StatusSummary, start_time: Instant) {
+fn print_test_summary(summary: &StatusSummary, start_time: Instant, to_stderr: bool) {
     let time_elapsed = start_time.elapsed();
 
-    let mut stdout = StandardStream::stdout(ColorChoice::Auto);
+    let mut output = if to_stderr {
+        StandardStream::stderr(ColorChoice::Auto)
+    } else {
+        StandardStream::stdout(ColorChoice::Auto)
+    };
 
-    writeln!(&mut stdout, "Ran {} tests in {:?}", summary.total, time_elapsed).unwrap();
-    write_conditional_colored(&mut stdout, || summary.passed > 0, Color::Green,
+    writeln!(&mut output, "Ran {} tests in {:?}", summary.total, time_elapsed).unwrap();
+    write_conditional_colored(&mut output, || summary.passed > 0, Color::Green,
         format!("    Passed: {}", summary.passed));
-    write_conditional_colored(&mut stdout, || summary.skipped > 0, Color::Yellow,
+    write_conditional_colored(&mut output, || summary.skipped > 0, Color::Yellow,
         format!("    Skipped: {}", summary.skipped));
-    write_conditional_colored(&mut stdout, || summary.pending > 0, Color::Yellow,
+    write_conditional_colored(&mut output, || summary.pending > 0, Color::Yellow,
         format!("    Pending: {}", summary.pending));
-    write_conditional_colored(&mut stdout, || summary.undefined > 0, Color::Red,
+    write_conditional_colored(&mut output, || summary.undefined > 0, Color::Red,
         format!("    Undefined: {}", summary.undefined));
-    write_conditional_colored(&mut stdout, || summary.ambiguous > 0, Color::Red,
+    write_conditional_colored(&mut output, || summary.ambiguous > 0, Color::Red,
         format!("    Ambiguous: {}", summary.ambiguous));
-    write_conditional_colored(&mut stdout, || summary.failed > 0, Color::Red,
+    write_conditional_colored(&mut output, || summary.failed > 0, Color::Red,
         format!("    Failed: {}", summary.failed));
-    writeln!(&mut stdout).unwrap();
+    if summary.flaky > 0 {
+        write_conditional_colored(&mut output, || true, Color::Yellow,
+            format!("    Flaky: {}", summary.flaky));
+    }
+    if summary.filtered_out > 0 {
+        writeln!(&mut output, "    Filtered out: {}", summary.filtered_out).unwrap();
+    }
+    writeln!(&mut output).unwrap();
+
+    if !summary.ambiguous_steps.is_empty() {
+        write_conditional_colored(&mut output, || true, Color::Red, "Ambiguous steps:".to_owned());
+        for ambiguous_step in &summary.ambiguous_steps {
+            writeln!(&mut output, "  {}", ambiguous_step.replace('\n', "\n  ")).unwrap();
+        }
+        writeln!(&mut output).unwrap();
+    }
+
+    if !summary.failed_hooks.is_empty() {
+        write_conditional_colored(&mut output, || true, Color::Red, "Failed global and feature hooks:".to_owned());
+        for failed_hook in &summary.failed_hooks {
+            writeln!(&mut output, "  {}", failed_hook.replace('\n', "\n  ")).unwrap();
+        }
+        writeln!(&mut output).unwrap();
+    }
+
+    if !summary.flaky_scenarios.is_empty() {
+        write_conditional_colored(&mut output, || true, Color::Yellow, "Flaky scenarios:".to_owned());
+        for flaky_scenario in &summary.flaky_scenarios {
+            writeln!(&mut output, "  {}", flaky_scenario).unwrap();
+        }
+        writeln!(&mut output).unwrap();
+    }
+
+    if !summary.snippets.is_empty() {
+        write_conditional_colored(&mut output, || true, Color::Yellow,
+            "You can implement missing steps with the snippets below:".to_owned());
+        writeln!(&mut output).unwrap();
+        for snippet in &summary.snippets {
+            writeln!(&mut output, "{}", snippet).unwrap();
+        }
+    }
 }
 
-fn write_conditional_colored<C: Fn() -> bool>(stdout: &mut StandardStream,
+fn write_conditional_colored<C: Fn() -> bool>(output: &mut StandardStream,
     condition: C, color: Color, text: String) {
 
     let condition_result = condition();
 
     if condition_result {
-        stdout.set_color(ColorSpec::new().set_fg(Some(color))).unwrap();
+        output.set_color(ColorSpec::new().set_fg(Some(color))).unwrap();
     }
-    stdout.write_all(text.as_bytes()).unwrap();
-    stdout.write_all(b"\n").unwrap();
+    output.write_all(text.as_bytes()).unwrap();
+    output.write_all(b"\n").unwrap();
     if condition_result {
-        stdout.set_color(ColorSpec::new().set_fg(None)).unwrap();
+        output.set_color(ColorSpec::new().set_fg(None)).unwrap();
     }
 }
@@ -0,0 +1,311 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serializer;
+use serde_json;
+
+use api::{TestResultStatus, TestStep};
+use api::event::{Event, EventListener};
+
+use super::report::{duration_nanos, seconds, write_file};
+
+const USAGE_TEXT_FILE_NAME: &str = "usage.txt";
+const USAGE_JSON_FILE_NAME: &str = "usage.json";
+
+/// Writes the usage of the step definitions to the `usage.txt` and `usage.json` files
+/// of the output directory.
+///
+/// For each step definition it lists the step texts it matched with their feature locations,
+/// the number of calls and the mean and max duration, so slow steps stand out.
+/// Step definitions that never matched are listed as unused, while the ones that only matched
+/// skipped steps (after a failure or in a dry run) are listed with zero calls.
+#[derive(Debug)]
+pub struct UsageFormatter {
+    text_path: PathBuf,
+    json_path: PathBuf,
+    /// The step definitions by code location.
+    step_definitions: Mutex<BTreeMap<String, UsageStepDefinition>>,
+}
+
+impl UsageFormatter {
+    pub fn new(output_dir: &Path) -> UsageFormatter {
+        UsageFormatter {
+            text_path: output_dir.join(USAGE_TEXT_FILE_NAME),
+            json_path: output_dir.join(USAGE_JSON_FILE_NAME),
+            step_definitions: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn write_usage_files(&self) -> io::Result<()> {
+        let step_definitions = self.step_definitions.lock().unwrap();
+        let usage = UsageReport::new(step_definitions.values());
+
+        write_file(&self.text_path, usage.to_text())?;
+        write_file(&self.json_path, serde_json::to_string_pretty(&usage)?)
+    }
+}
+
+impl EventListener for UsageFormatter {
+    fn on_event(&self, event: &Event) {
+        match *event {
+            Event::GlueLoaded { step_definitions, .. } => {
+                let mut usage_step_definitions = self.step_definitions.lock().unwrap();
+                for step_definition in step_definitions {
+                    let location = step_definition.location.to_string();
+                    usage_step_definitions.insert(location.clone(), UsageStepDefinition {
+                        pattern: step_definition.expression.to_owned(),
+                        location,
+                        steps: BTreeMap::new(),
+                    });
+                }
+            },
+            Event::TestStepFinished { test_step: TestStep::Cuke(cuke_step_test_step), result, .. } => {
+                let location = match cuke_step_test_step.get_code_location() {
+                    Some(location) => location.to_string(),
+                    None => return,
+                };
+
+                let mut usage_step_definitions = self.step_definitions.lock().unwrap();
+                if let Some(usage_step_definition) = usage_step_definitions.get_mut(&location) {
+                    let usage_step = usage_step_definition.steps
+                        .entry(cuke_step_test_step.get_step_text().to_owned())
+                        .or_insert_with(UsageStep::default);
+                    usage_step.locations.insert(cuke_step_test_step.get_step_location());
+                    // skipped steps matched the step definition, but did not call it
+                    if result.status != TestResultStatus::Skipped {
+                        usage_step.durations.push(result.duration.unwrap_or_default());
+                    }
+                }
+            },
+            Event::TestRunFinished { .. } => {
+                if let Err(err) = self.write_usage_files() {
+                    panic!("could not write usage report \"{}\": {}", self.text_path.display(), err);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+#[derive(Debug)]
+struct UsageStepDefinition {
+    pattern: String,
+    location: String,
+    /// The matched steps by step text.
+    steps: BTreeMap<String, UsageStep>,
+}
+
+#[derive(Debug, Default)]
+struct UsageStep {
+    /// The feature locations like `tests/features/calculator.feature:9`.
+    locations: BTreeSet<String>,
+    /// The duration of each call, without the skipped steps.
+    durations: Vec<Duration>,
+}
+
+#[derive(Debug, Serialize)]
+struct UsageReport {
+    /// The used step definitions, the slowest (by mean duration) first.
+    step_definitions: Vec<UsageStepDefinitionStats>,
+    /// The locations and patterns of the step definitions that never matched.
+    unused_step_definitions: Vec<UsageUnusedStepDefinition>,
+}
+
+#[derive(Debug, Serialize)]
+struct UsageStepDefinitionStats {
+    pattern: String,
+    location: String,
+    #[serde(flatten)]
+    stats: UsageStats,
+    steps: Vec<UsageStepStats>,
+}
+
+#[derive(Debug, Serialize)]
+struct UsageStepStats {
+    text: String,
+    locations: Vec<String>,
+    #[serde(flatten)]
+    stats: UsageStats,
+}
+
+#[derive(Debug, Serialize)]
+struct UsageUnusedStepDefinition {
+    pattern: String,
+    location: String,
+}
+
+#[derive(Debug, Copy, Clone, Serialize)]
+struct UsageStats {
+    calls: usize,
+    /// Serialized in nanoseconds.
+    #[serde(serialize_with = "serialize_nanos")]
+    mean_duration: Duration,
+    /// Serialized in nanoseconds.
+    #[serde(serialize_with = "serialize_nanos")]
+    max_duration: Duration,
+}
+
+impl UsageStats {
+    fn new(durations: &[Duration]) -> UsageStats {
+        let total = durations.iter().sum::<Duration>();
+
+        UsageStats {
+            calls: durations.len(),
+            mean_duration: if durations.is_empty() { Duration::default() } else { total / durations.len() as u32 },
+            max_duration: durations.iter().max().cloned().unwrap_or_default(),
+        }
+    }
+}
+
+impl UsageReport {
+    fn new<'a, I: Iterator<Item=&'a UsageStepDefinition>>(usage_step_definitions: I) -> UsageReport {
+        let mut step_definitions = Vec::new();
+        let mut unused_step_definitions = Vec::new();
+
+        for usage_step_definition in usage_step_definitions {
+            if usage_step_definition.steps.is_empty() {
+                unused_step_definitions.push(UsageUnusedStepDefinition {
+                    pattern: usage_step_definition.pattern.clone(),
+                    location: usage_step_definition.location.clone(),
+                });
+                continue;
+            }
+
+            let durations = usage_step_definition.steps.values()
+                .flat_map(|usage_step| usage_step.durations.iter().cloned())
+                .collect::<Vec<Duration>>();
+            let mut steps = usage_step_definition.steps.iter()
+                .map(|(text, usage_step)| UsageStepStats {
+                    text: text.clone(),
+                    locations: usage_step.locations.iter().cloned().collect(),
+                    stats: UsageStats::new(&usage_step.durations),
+                })
+                .collect::<Vec<UsageStepStats>>();
+            steps.sort_by_key(|step| ::std::cmp::Reverse(step.stats.mean_duration));
+
+            step_definitions.push(UsageStepDefinitionStats {
+                pattern: usage_step_definition.pattern.clone(),
+                location: usage_step_definition.location.clone(),
+                stats: UsageStats::new(&durations),
+                steps,
+            });
+        }
+        step_definitions.sort_by_key(|step_definition| ::std::cmp::Reverse(step_definition.stats.mean_duration));
+
+        UsageReport {
+            step_definitions,
+            unused_step_definitions,
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        for step_definition in &self.step_definitions {
+            writeln!(text, "{} # {}", step_definition.pattern, step_definition.location).unwrap();
+            writeln!(text, "  {}", format_stats(step_definition.stats)).unwrap();
+
+            for step in &step_definition.steps {
+                writeln!(text, "    {} ({})", step.text, format_stats(step.stats)).unwrap();
+                for location in &step.locations {
+                    writeln!(text, "      {}", location).unwrap();
+                }
+            }
+        }
+
+        if !self.unused_step_definitions.is_empty() {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str("Unused step definitions:\n");
+            for step_definition in &self.unused_step_definitions {
+                writeln!(text, "  {} # {}", step_definition.pattern, step_definition.location).unwrap();
+            }
+        }
+
+        text
+    }
+}
+
+fn format_stats(stats: UsageStats) -> String {
+    format!("{} {}, mean {}s, max {}s", stats.calls, if stats.calls == 1 { "call" } else { "calls" },
+        seconds(stats.mean_duration), seconds(stats.max_duration))
+}
+
+fn serialize_nanos<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration_nanos(*duration))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step_definition(pattern: &str, location: &str, steps: &[(&str, &str, &[u64])]) -> UsageStepDefinition {
+        UsageStepDefinition {
+            pattern: pattern.to_owned(),
+            location: location.to_owned(),
+            steps: steps.iter()
+                .map(|&(text, location, millis)| (text.to_owned(), UsageStep {
+                    locations: vec![location.to_owned()].into_iter().collect(),
+                    durations: millis.iter().map(|millis| Duration::from_millis(*millis)).collect(),
+                }))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn stats_have_the_mean_and_max_duration() {
+        let stats = UsageStats::new(&[Duration::from_millis(10), Duration::from_millis(30)]);
+
+        assert_eq!(stats.calls, 2);
+        assert_eq!(stats.mean_duration, Duration::from_millis(20));
+        assert_eq!(stats.max_duration, Duration::from_millis(30));
+    }
+
+    #[test]
+    fn step_definitions_that_only_matched_skipped_steps_are_not_unused() {
+        let step_definitions = vec![
+            step_definition("the result is {int}", "tests/steps/calculator.rs:28", &[
+                ("the result is 3", "tests/features/calculator.feature:10", &[]),
+            ]),
+        ];
+
+        assert_eq!(UsageReport::new(step_definitions.iter()).to_text(), "\
+the result is {int} # tests/steps/calculator.rs:28
+  0 calls, mean 0.000s, max 0.000s
+    the result is 3 (0 calls, mean 0.000s, max 0.000s)
+      tests/features/calculator.feature:10
+");
+    }
+
+    #[test]
+    fn slowest_step_definitions_come_first_and_unused_ones_last() {
+        let step_definitions = vec![
+            step_definition("I add {int} and {int}", "tests/steps/calculator.rs:12", &[
+                ("I add 1 and 2", "tests/features/calculator.feature:9", &[1, 3]),
+            ]),
+            step_definition("I divide {int} by {int}", "tests/steps/calculator.rs:20", &[]),
+            step_definition("the result is {int}", "tests/steps/calculator.rs:28", &[
+                ("the result is 3", "tests/features/calculator.feature:10", &[500]),
+            ]),
+        ];
+
+        assert_eq!(UsageReport::new(step_definitions.iter()).to_text(), "\
+the result is {int} # tests/steps/calculator.rs:28
+  1 call, mean 0.500s, max 0.500s
+    the result is 3 (1 call, mean 0.500s, max 0.500s)
+      tests/features/calculator.feature:10
+I add {int} and {int} # tests/steps/calculator.rs:12
+  2 calls, mean 0.002s, max 0.003s
+    I add 1 and 2 (2 calls, mean 0.002s, max 0.003s)
+      tests/features/calculator.feature:9
+
+Unused step definitions:
+  I divide {int} by {int} # tests/steps/calculator.rs:20
+");
+    }
+}
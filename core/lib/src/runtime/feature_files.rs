@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use glob::{self, Pattern};
+use walkdir::WalkDir;
+
+use config;
+use error::{Error, Result};
+
+const FEATURE_FILE_EXTENSION: &str = ".feature";
+const GLOB_PATTERN_CHARS: &[char] = &['*', '?', '['];
+
+/// Finds the feature files of the `features` paths (directories, feature files or glob patterns)
+/// that do not match any of the `exclude_features` glob patterns.
+///
+/// Every feature file is only returned once, even if it is selected by multiple paths.
+pub fn find(features: &[PathBuf], exclude_features: &[String]) -> Result<Vec<PathBuf>> {
+    let exclude_patterns = exclude_features.iter()
+        .map(|exclude_feature| Pattern::new(exclude_feature)
+            .map_err(|err| invalid_value("exclude_features", exclude_feature, err.to_string())))
+        .collect::<Result<Vec<Pattern>>>()?;
+
+    let mut feature_files = Vec::new();
+    let mut canonical_feature_files = HashSet::new();
+
+    for path in features {
+        for feature_file in find_in_path(path)? {
+            let excluded = exclude_patterns.iter()
+                .any(|exclude_pattern| exclude_pattern.matches_path(&feature_file));
+            let canonical_feature_file = fs::canonicalize(&feature_file)
+                .unwrap_or_else(|_| feature_file.clone());
+
+            if !excluded && canonical_feature_files.insert(canonical_feature_file) {
+                feature_files.push(feature_file);
+            }
+        }
+    }
+
+    Ok(feature_files)
+}
+
+fn find_in_path(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_dir() {
+        return find_in_dir(path);
+    }
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let pattern = path.to_string_lossy();
+    if !pattern.contains(GLOB_PATTERN_CHARS) {
+        return Err(invalid_value("features", &pattern, "the path does not exist".to_owned()));
+    }
+
+    let paths = glob::glob(&pattern)
+        .map_err(|err| invalid_value("features", &pattern, err.to_string()))?;
+
+    let mut feature_files = Vec::new();
+    for path in paths {
+        let path = path.map_err(|err| Error::Io(err.into_error()))?;
+
+        if path.is_dir() {
+            feature_files.extend(find_in_dir(&path)?);
+        } else if is_feature_file(&path) {
+            feature_files.push(path);
+        }
+    }
+
+    Ok(feature_files)
+}
+
+fn find_in_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut feature_files = Vec::new();
+
+    for entry in WalkDir::new(dir).follow_links(true) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            // The files of a symlink cycle are found through the ancestor directory already.
+            Err(ref err) if err.loop_ancestor().is_some() => continue,
+            Err(err) => return Err(Error::WalkDir(err)),
+        };
+
+        if entry.file_type().is_file() && is_feature_file(entry.path()) {
+            feature_files.push(entry.into_path());
+        }
+    }
+
+    Ok(feature_files)
+}
+
+fn is_feature_file(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(FEATURE_FILE_EXTENSION)
+}
+
+fn invalid_value(setting: &str, value: &str, message: String) -> Error {
+    Error::Config(config::Error::InvalidValue {
+        setting: setting.to_owned(),
+        value: value.to_owned(),
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::ops::Deref;
+    use std::process;
+
+    /// A temporary directory of a test, which is removed when the test finishes.
+    struct TestDir(PathBuf);
+
+    impl Deref for TestDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn create_test_dir(name: &str, files: &[&str]) -> TestDir {
+        // The process id keeps concurrent test runs from sharing the directory.
+        let test_dir = TestDir(env::temp_dir()
+            .join(format!("cuke_runner_feature_files_{}_{}", name, process::id())));
+        let _ = fs::remove_dir_all(&*test_dir);
+
+        for file in files {
+            let path = test_dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "Feature: Test").unwrap();
+        }
+
+        test_dir
+    }
+
+    fn file_names(feature_files: &[PathBuf]) -> Vec<String> {
+        let mut file_names = feature_files.iter()
+            .map(|feature_file| feature_file.file_name().unwrap().to_string_lossy().into_owned())
+            .collect::<Vec<String>>();
+        file_names.sort();
+        file_names
+    }
+
+    #[test]
+    fn finds_feature_files_of_dirs_files_and_glob_patterns_once() {
+        let test_dir = create_test_dir("paths", &[
+            "shared/login.feature",
+            "shared/wip/draft.feature",
+            "crate/calculator.feature",
+            "crate/notes.txt",
+        ]);
+
+        let feature_files = find(&[
+            test_dir.join("shared"),
+            test_dir.join("shared/login.feature"),
+            test_dir.join("crate/*"),
+        ], &["**/wip/*".to_owned()]).unwrap();
+
+        assert_eq!(file_names(&feature_files), vec!["calculator.feature", "login.feature"]);
+    }
+
+    #[test]
+    fn missing_path_is_rejected() {
+        let test_dir = create_test_dir("missing", &[]);
+
+        assert!(find(&[test_dir.join("missing.feature")], &[]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_cycles_are_skipped() {
+        let test_dir = create_test_dir("symlink_cycle", &["features/calculator.feature"]);
+        ::std::os::unix::fs::symlink(test_dir.join("features"), test_dir.join("features/cycle")).unwrap();
+
+        let feature_files = find(&[test_dir.join("features")], &[]).unwrap();
+
+        assert_eq!(file_names(&feature_files), vec!["calculator.feature"]);
+    }
+}
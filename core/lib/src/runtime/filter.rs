@@ -0,0 +1,331 @@
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use gherkin::cuke::Cuke;
+use regex::Regex;
+
+use Config;
+use glue::hook::TagPredicate;
+
+/// Selects the cukes to run, based on the filters of the `Config`.
+#[derive(Debug)]
+pub struct CukeFilter {
+    tag_predicates: Vec<TagPredicate>,
+    feature_locations: Vec<FeatureLocation>,
+    name_regexes: Vec<Regex>,
+}
+
+impl CukeFilter {
+    pub fn new(config: &Config) -> CukeFilter {
+        let tag_predicates = config.tags.iter()
+            .map(|tag_expression| match TagPredicate::new(tag_expression) {
+                Ok(tag_predicate) => tag_predicate,
+                Err(err) => panic!("tag expression \"{}\" is invalid: {}", tag_expression, err),
+            })
+            .collect();
+        let feature_locations = config.feature_locations.iter()
+            .map(|feature_location| match feature_location.parse() {
+                Ok(feature_location) => feature_location,
+                Err(err) => panic!("feature location \"{}\" is invalid: {}", feature_location, err),
+            })
+            .collect();
+        let name_regexes = config.names.iter()
+            .map(|name| match Regex::new(name) {
+                Ok(name_regex) => name_regex,
+                Err(err) => panic!("name regex \"{}\" is invalid: {}", name, err),
+            })
+            .collect();
+
+        CukeFilter {
+            tag_predicates,
+            feature_locations,
+            name_regexes,
+        }
+    }
+
+    /// Whether the feature file contains cukes that can be selected by the feature locations.
+    pub fn matches_feature_file(&self, path: &Path) -> bool {
+        self.feature_locations.is_empty() || self.feature_locations.iter()
+            .any(|feature_location| feature_location.matches_path(path))
+    }
+
+    /// Whether the cuke matches all tag expressions, (at least) one of the feature locations
+    /// and (at least) one of the name regexes.
+    ///
+    /// The tags of a cuke include the tags of its feature, scenario and examples.
+    pub fn matches(&self, uri: &str, cuke: &Cuke) -> bool {
+        let lines = cuke.locations.iter()
+            .map(|location| location.line)
+            .collect::<Vec<u32>>();
+
+        let matches_feature_locations = self.feature_locations.is_empty() || self.feature_locations.iter()
+            .any(|feature_location| {
+                feature_location.matches_path(Path::new(uri)) && feature_location.matches_lines(&lines)
+            });
+
+        let matches_names = self.name_regexes.is_empty() || self.name_regexes.iter()
+            .any(|name_regex| name_regex.is_match(&cuke.name));
+
+        matches_feature_locations && matches_names && self.tag_predicates.iter()
+            .all(|tag_predicate| tag_predicate.test(&cuke.tags))
+    }
+}
+
+/// A feature file location like `path/to/file.feature:12:30`.
+///
+/// It selects the cukes of the feature file that are located at one of the lines,
+/// or all cukes of the feature file if no lines are given.
+/// The line of a Scenario Outline selects all of its examples,
+/// the line of an Examples table row only the cuke of this row.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FeatureLocation {
+    path: PathBuf,
+    lines: Vec<u32>,
+}
+
+impl FeatureLocation {
+    pub fn new(path: PathBuf, lines: Vec<u32>) -> FeatureLocation {
+        FeatureLocation {
+            path,
+            lines,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the path of the feature file ends with the path of the location,
+    /// so relative locations also select the feature file.
+    pub fn matches_path(&self, path: &Path) -> bool {
+        path.ends_with(&self.path)
+    }
+
+    pub fn matches_lines(&self, lines: &[u32]) -> bool {
+        self.lines.is_empty() || lines.iter().any(|line| self.lines.contains(line))
+    }
+}
+
+impl FromStr for FeatureLocation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<FeatureLocation, String> {
+        let mut parts = s.split(':').collect::<Vec<&str>>();
+        let mut lines = Vec::new();
+
+        // Only trailing numbers are lines, so paths can contain colons (like `C:\features`).
+        while parts.len() > 1 {
+            match parts[parts.len() - 1].parse::<u32>() {
+                Ok(line) => {
+                    lines.push(line);
+                    parts.pop();
+                },
+                Err(_) => break,
+            }
+        }
+        lines.reverse();
+
+        let path = parts.join(":");
+        if path.is_empty() {
+            return Err("the path of the feature file is missing".to_owned());
+        }
+
+        Ok(FeatureLocation {
+            path: normalize(Path::new(&path)),
+            lines,
+        })
+    }
+}
+
+/// Drops the `.` components of the path and resolves its `..` components, because they
+/// never match the components of a feature file path (see `FeatureLocation::matches_path`).
+///
+/// Leading `..` components are dropped as well, so `../tests/calculator.feature` is matched
+/// like `tests/calculator.feature`.
+fn normalize(path: &Path) -> PathBuf {
+    let mut components = Vec::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => {
+                if let Some(Component::Normal(_)) = components.last() {
+                    components.pop();
+                }
+            },
+            _ => components.push(component),
+        }
+    }
+
+    components.iter().collect()
+}
+
+impl fmt::Display for FeatureLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.path.display(), f)?;
+        for line in &self.lines {
+            write!(f, ":{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the feature locations of a rerun file (see `RerunFormatter`), one per line.
+pub fn parse_rerun_file(source: &str) -> Result<Vec<FeatureLocation>, String> {
+    source.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.parse::<FeatureLocation>()
+            .map_err(|err| format!("\"{}\": {}", line, err)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEATURE: &str = "\
+Feature: Calculator
+
+  Scenario: Add two numbers
+    Given a calculator
+
+  @slow
+  Scenario: Add many numbers
+    Given a calculator
+
+  Scenario Outline: Divide numbers
+    Given a calculator
+
+    Examples:
+      | a |
+      | 1 |
+
+    @slow
+    Examples:
+      | a |
+      | 2 |
+";
+
+    fn cuke_filter(tag_expressions: &[&str], names: &[&str]) -> CukeFilter {
+        CukeFilter {
+            tag_predicates: tag_expressions.iter()
+                .map(|tag_expression| TagPredicate::new(tag_expression).unwrap())
+                .collect(),
+            feature_locations: Vec::new(),
+            name_regexes: names.iter()
+                .map(|name| Regex::new(name).unwrap())
+                .collect(),
+        }
+    }
+
+    /// The names and lines of the cukes of `FEATURE` that match the filter.
+    fn matching_cukes(cuke_filter: &CukeFilter) -> Vec<(String, u32)> {
+        let document = gherkin::Parser::default().parse_str(FEATURE).unwrap();
+
+        gherkin::cuke::Compiler::default().compile(&document).iter()
+            .filter(|cuke| cuke_filter.matches("tests/features/calculator.feature", cuke))
+            .map(|cuke| (cuke.name.to_string(), cuke.locations[0].line))
+            .collect()
+    }
+
+    #[test]
+    fn names_select_the_cukes_with_a_matching_name() {
+        assert_eq!(matching_cukes(&cuke_filter(&[], &["^Add"])), vec![
+            ("Add two numbers".to_owned(), 3),
+            ("Add many numbers".to_owned(), 7),
+        ]);
+        assert_eq!(matching_cukes(&cuke_filter(&[], &["two", "many"])), vec![
+            ("Add two numbers".to_owned(), 3),
+            ("Add many numbers".to_owned(), 7),
+        ]);
+    }
+
+    #[test]
+    fn names_and_tags_must_both_match() {
+        assert_eq!(matching_cukes(&cuke_filter(&["not @slow"], &["^Add"])), vec![
+            ("Add two numbers".to_owned(), 3),
+        ]);
+        assert_eq!(matching_cukes(&cuke_filter(&["@slow"], &["two"])), vec![]);
+    }
+
+    #[test]
+    fn names_select_all_examples_of_a_scenario_outline_unless_filtered_by_tags() {
+        assert_eq!(matching_cukes(&cuke_filter(&[], &["^Divide"])), vec![
+            ("Divide numbers".to_owned(), 15),
+            ("Divide numbers".to_owned(), 20),
+        ]);
+        assert_eq!(matching_cukes(&cuke_filter(&["not @slow"], &["^Divide"])), vec![
+            ("Divide numbers".to_owned(), 15),
+        ]);
+    }
+
+    #[test]
+    fn feature_location_without_lines_matches_all_lines() {
+        let feature_location = "features/calculator.feature".parse::<FeatureLocation>().unwrap();
+
+        assert!(feature_location.matches_path(Path::new("/project/tests/features/calculator.feature")));
+        assert!(feature_location.matches_lines(&[3]));
+        assert!(feature_location.matches_lines(&[8, 14]));
+    }
+
+    #[test]
+    fn feature_location_with_lines_matches_any_of_the_lines() {
+        let feature_location = "calculator.feature:12:30".parse::<FeatureLocation>().unwrap();
+
+        assert!(feature_location.matches_lines(&[12]));
+        assert!(feature_location.matches_lines(&[25, 30]));
+        assert!(!feature_location.matches_lines(&[13]));
+    }
+
+    #[test]
+    fn feature_location_does_not_match_other_feature_files() {
+        let feature_location = "calculator.feature:12".parse::<FeatureLocation>().unwrap();
+
+        assert!(!feature_location.matches_path(Path::new("/project/tests/features/rpn_calculator.feature")));
+        assert!(!feature_location.matches_path(Path::new("/project/tests/features/calculator.feature.md")));
+    }
+
+    #[test]
+    fn feature_location_path_is_normalized() {
+        let feature_location = "./tests/features/calculator.feature:8".parse::<FeatureLocation>().unwrap();
+
+        assert_eq!(feature_location.path(), Path::new("tests/features/calculator.feature"));
+        assert!(feature_location.matches_path(Path::new("/project/tests/features/calculator.feature")));
+
+        let feature_location = "tests/../tests/./features/calculator.feature".parse::<FeatureLocation>().unwrap();
+
+        assert_eq!(feature_location.path(), Path::new("tests/features/calculator.feature"));
+    }
+
+    #[test]
+    fn feature_location_path_can_contain_colons() {
+        let feature_location = "C:\\features\\calculator.feature:7".parse::<FeatureLocation>().unwrap();
+
+        assert_eq!(feature_location, FeatureLocation {
+            path: PathBuf::from("C:\\features\\calculator.feature"),
+            lines: vec![7],
+        });
+    }
+
+    #[test]
+    fn feature_location_without_path_is_invalid() {
+        assert!(":12".parse::<FeatureLocation>().is_err());
+    }
+
+    #[test]
+    fn rerun_file_is_parsed_into_the_written_feature_locations() {
+        let feature_locations = vec![
+            FeatureLocation::new(PathBuf::from("tests/features/calculator.feature"), vec![12, 30]),
+            FeatureLocation::new(PathBuf::from("tests/features/rpn_calculator.feature"), vec![7]),
+        ];
+        let rerun_file = feature_locations.iter()
+            .map(|feature_location| format!("{}\n", feature_location))
+            .collect::<String>();
+
+        assert_eq!(rerun_file, "tests/features/calculator.feature:12:30\n\
+            tests/features/rpn_calculator.feature:7\n");
+        assert_eq!(parse_rerun_file(&format!("{}\n\n", rerun_file)), Ok(feature_locations));
+    }
+}
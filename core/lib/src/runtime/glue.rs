@@ -1,3 +1,4 @@
+use std::cmp::Reverse;
 use std::collections::HashMap;
 
 use gherkin::cuke;
@@ -5,36 +6,67 @@ use gherkin::cuke;
 use glue::StaticGlueDefinitions;
 use runtime::{
     AmbiguousCukeStepDefinitionMatch, HookDefinition, CukeStepDefinitionMatch,
-    StepDefinition, StepDefinitionMatch, UndefinedCukeStepDefinitionMatch,
+    ParameterTypeRegistry, StepDefinition, StepDefinitionMatch, UndefinedCukeStepDefinitionMatch,
 };
 
 #[derive(Debug)]
 pub struct Glue {
+    before_all_hooks: Vec<HookDefinition>,
+    before_feature_hooks: Vec<HookDefinition>,
     before_scenario_hooks: Vec<HookDefinition>,
     before_step_hooks: Vec<HookDefinition>,
     step_definitions_by_pattern: HashMap<&'static str, StepDefinition>,
     after_step_hooks: Vec<HookDefinition>,
     after_scenario_hooks: Vec<HookDefinition>,
+    after_feature_hooks: Vec<HookDefinition>,
+    after_all_hooks: Vec<HookDefinition>,
 }
 
 impl From<&[StaticGlueDefinitions]> for Glue {
     fn from(static_glue_definitions: &[StaticGlueDefinitions]) -> Glue {
-        let before_scenario_hooks = static_glue_definitions.iter()
+        // The sort is stable, so hooks with the same order keep their definition order.
+        let mut before_all_hooks = static_glue_definitions.iter()
+            .flat_map(|glue| glue.before_all_hooks.iter())
+            .map(HookDefinition::from)
+            .collect::<Vec<HookDefinition>>();
+        before_all_hooks.sort_by_key(HookDefinition::get_order);
+        let mut before_feature_hooks = static_glue_definitions.iter()
+            .flat_map(|glue| glue.before_feature_hooks.iter())
+            .map(HookDefinition::from)
+            .collect::<Vec<HookDefinition>>();
+        before_feature_hooks.sort_by_key(HookDefinition::get_order);
+        let mut before_scenario_hooks = static_glue_definitions.iter()
             .flat_map(|glue| glue.before_scenario_hooks.iter())
             .map(HookDefinition::from)
-            .collect();
-        let before_step_hooks = static_glue_definitions.iter()
+            .collect::<Vec<HookDefinition>>();
+        before_scenario_hooks.sort_by_key(HookDefinition::get_order);
+        let mut before_step_hooks = static_glue_definitions.iter()
             .flat_map(|glue| glue.before_step_hooks.iter())
             .map(HookDefinition::from)
-            .collect();
-        let after_step_hooks = static_glue_definitions.iter()
+            .collect::<Vec<HookDefinition>>();
+        before_step_hooks.sort_by_key(HookDefinition::get_order);
+        let mut after_step_hooks = static_glue_definitions.iter()
             .flat_map(|glue| glue.after_step_hooks.iter())
             .map(HookDefinition::from)
-            .collect();
-        let after_scenario_hooks = static_glue_definitions.iter()
+            .collect::<Vec<HookDefinition>>();
+        after_step_hooks.sort_by_key(|hook| Reverse(hook.get_order()));
+        let mut after_scenario_hooks = static_glue_definitions.iter()
             .flat_map(|glue| glue.after_scenario_hooks.iter())
             .map(HookDefinition::from)
-            .collect();
+            .collect::<Vec<HookDefinition>>();
+        after_scenario_hooks.sort_by_key(|hook| Reverse(hook.get_order()));
+        let mut after_feature_hooks = static_glue_definitions.iter()
+            .flat_map(|glue| glue.after_feature_hooks.iter())
+            .map(HookDefinition::from)
+            .collect::<Vec<HookDefinition>>();
+        after_feature_hooks.sort_by_key(|hook| Reverse(hook.get_order()));
+        let mut after_all_hooks = static_glue_definitions.iter()
+            .flat_map(|glue| glue.after_all_hooks.iter())
+            .map(HookDefinition::from)
+            .collect::<Vec<HookDefinition>>();
+        after_all_hooks.sort_by_key(|hook| Reverse(hook.get_order()));
+
+        let parameter_type_registry = ParameterTypeRegistry::from(static_glue_definitions);
 
         let step_definitions_capacity = static_glue_definitions.iter()
             .flat_map(|glue| glue.steps.iter())
@@ -42,8 +74,10 @@ impl From<&[StaticGlueDefinitions]> for Glue {
         let mut step_definitions_by_pattern = HashMap::with_capacity(step_definitions_capacity);
         static_glue_definitions.iter()
             .flat_map(|glue| glue.steps.iter())
-            .map(|static_step_definition|
-                (static_step_definition.expression, StepDefinition::from(static_step_definition)))
+            .map(|static_step_definition| (
+                static_step_definition.expression,
+                StepDefinition::new(static_step_definition, &parameter_type_registry),
+            ))
             .for_each(|(expression, step_definition)| {
                 let new_location = step_definition.location;
 
@@ -51,16 +85,20 @@ impl From<&[StaticGlueDefinitions]> for Glue {
                     let prev_location = prev.location;
                     panic!("duplicate step definition \"{}\":
    first: {}
-  second: {}", prev.expression.regex.as_str(), prev_location, new_location)
+  second: {}", prev.expression.as_str(), prev_location, new_location)
                 }
             });
 
         Glue {
+            before_all_hooks,
+            before_feature_hooks,
             before_scenario_hooks,
             before_step_hooks,
             step_definitions_by_pattern,
             after_step_hooks,
             after_scenario_hooks,
+            after_feature_hooks,
+            after_all_hooks,
         }
     }
 }
@@ -70,6 +108,14 @@ impl Glue {
         &self.step_definitions_by_pattern
     }
 
+    pub fn get_before_all_hooks(&self) -> &Vec<HookDefinition> {
+        &self.before_all_hooks
+    }
+
+    pub fn get_before_feature_hooks(&self) -> &Vec<HookDefinition> {
+        &self.before_feature_hooks
+    }
+
     pub fn get_before_scenario_hooks(&self) -> &Vec<HookDefinition> {
         &self.before_scenario_hooks
     }
@@ -86,6 +132,14 @@ impl Glue {
         &self.after_scenario_hooks
     }
 
+    pub fn get_after_feature_hooks(&self) -> &Vec<HookDefinition> {
+        &self.after_feature_hooks
+    }
+
+    pub fn get_after_all_hooks(&self) -> &Vec<HookDefinition> {
+        &self.after_all_hooks
+    }
+
     pub fn step_definition_match<'s, 'a: 's>(&'a self, feature_path: &str, step: &'s cuke::Step)
         -> StepDefinitionMatch<'s> {
 
@@ -111,9 +165,18 @@ impl Glue {
             });
         }
         if matches.len() > 1 {
+            let mut step_definitions = matches.into_iter()
+                .map(|step_definition_match| step_definition_match.step_definition)
+                .collect::<Vec<StepDefinition>>();
+            step_definitions.sort_by_key(|step_definition| {
+                let location = step_definition.get_location();
+                (location.file_path, location.line_number)
+            });
+
             return StepDefinitionMatch::Ambiguous(AmbiguousCukeStepDefinitionMatch {
                 feature_path: feature_path.to_owned(),
                 step,
+                step_definitions,
                 arguments: Vec::new(),
             });
         }
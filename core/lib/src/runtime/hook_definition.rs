@@ -6,12 +6,12 @@ use api::CodeLocation;
 use error::Result;
 use glue::hook::{HookFn, StaticHookDef};
 use glue::hook::TagPredicate;
-use runtime::Scenario;
+use glue::scenario::Scenario;
 
 #[derive(Clone)]
 pub struct HookDefinition {
     tag_predicate: TagPredicate,
-    order: u32,
+    order: isize,
 //    timeout: Option<Duration>,
     hook_fn: HookFn,
     location: CodeLocation,
@@ -40,7 +40,7 @@ impl From<&&StaticHookDef> for HookDefinition {
 
         HookDefinition {
             tag_predicate,
-            order: 0,
+            order: static_hook_def.order,
             hook_fn: static_hook_def.hook_fn,
             location: static_hook_def.location,
         }
@@ -52,8 +52,13 @@ impl HookDefinition {
         &self.location
     }
 
+    /// The tag expression restricting when the hook is executed.
+    pub fn get_tag_expression(&self) -> &str {
+        self.tag_predicate.as_str()
+    }
+
     pub fn execute(&self, scenario: &mut Scenario) -> Result<()> {
-        let result = (self.hook_fn)(&mut scenario.glue_scenario);
+        let result = (self.hook_fn)(scenario);
         match result {
             Ok(_) => Ok(()),
             Err(error) => Err(::error::Error::Execution(error)),
@@ -64,7 +69,14 @@ impl HookDefinition {
         self.tag_predicate.test(tags)
     }
 
-    pub fn get_order(&self) -> u32 {
+    pub fn matches_tag_names(&self, tag_names: &[&str]) -> bool {
+        self.tag_predicate.test_names(tag_names)
+    }
+
+    /// The execution order among the hooks of the same hook type.
+    ///
+    /// Before hooks are executed in ascending order and after hooks in descending order.
+    pub fn get_order(&self) -> isize {
         self.order
     }
 }
@@ -1,100 +1,215 @@
 use std::time::SystemTime;
 use std::fs;
-use std::collections::HashMap;
-use {Config, ExecutionMode};
+use {Config, ExecutionMode, Order};
+use config::{ConfigOverrides, Formatter};
 use runner::{EventBus, SyncEventBus, EventPublisher, Runner};
-use self::event_listener::{TestSummaryListener, SyncTestSummaryListener, ExitStatusListener, SyncExitStatusListener};
+use self::event_listener::{
+    TestSummaryListener, SyncTestSummaryListener, ExitStatusListener, SyncExitStatusListener,
+    HtmlFormatter, JsonFormatter, JUnitFormatter, NdjsonFormatter, PrettyFormatter, ProgressBarListener,
+    RerunFormatter, TapFormatter, UsageFormatter,
+};
 use crate::api::event::{Event, EventListener, SyncEventListener};
-use gherkin::ast::GherkinDocument;
+use gherkin::ast::{Feature, GherkinDocument};
 use gherkin::cuke::Cuke;
-use walkdir::{DirEntry, WalkDir};
 use rayon::prelude::*;
 pub use self::glue::*;
 pub use self::hook_definition::*;
+pub use self::parameter_type::*;
 pub use self::scenario::*;
 pub use self::step_definition::*;
 pub use self::step_definition_match::*;
 pub use self::step_expression::*;
 pub use self::test_case::*;
+use self::filter::CukeFilter;
 
 mod glue;
 mod step_definition;
 mod hook_definition;
+mod parameter_type;
 mod step_expression;
 pub mod test_case;
 mod scenario;
 mod step_definition_match;
+mod snippet;
+mod filter;
+mod feature_files;
+mod order;
 pub mod event_listener;
 
 
 pub fn run(glue: Glue, config: Config) -> i32 {
-    let runner = Runner::new(glue, config.dry_run);
+    let config_overrides = match ConfigOverrides::load() {
+        Ok(config_overrides) => config_overrides,
+        Err(err) => panic!("could not read cuke runner settings: {}", err),
+    };
+    let mut config = match config_overrides.apply(config) {
+        Ok(config) => config,
+        Err(err) => panic!("could not apply cuke runner settings: {}", err),
+    };
+    select_rerun_file_locations(&mut config);
+    config.order = order::with_seed(config.order);
+    print_random_seed(config.order);
+    let formatters = config_overrides.formatters.clone().unwrap_or_default();
+    // The TAP stream on stdout must not be mixed with the test summary and snippets.
+    let summary_to_stderr = formatters.contains(&Formatter::Tap);
+
+    let runner = Runner::new(glue, &config);
 
     match config.execution_mode {
         ExecutionMode::Sequential { event_listeners } => {
             let exit_status_listener = ExitStatusListener::new();
             let test_summary_listener = TestSummaryListener::new();
+            let formatter_listeners = formatters.iter()
+                .map(|formatter| create_formatter(*formatter, &config))
+                .collect::<Vec<Box<EventListener>>>();
 
-            let mut listeners: Vec<&EventListener> = Vec::with_capacity(2 + event_listeners.len());
+            let mut listeners: Vec<&EventListener> = Vec::with_capacity(
+                2 + event_listeners.len() + formatter_listeners.len());
             listeners.push(&exit_status_listener);
             listeners.push(&test_summary_listener);
 
             for event_listener in event_listeners {
                 listeners.push(*event_listener);
             }
+            for formatter_listener in &formatter_listeners {
+                listeners.push(&**formatter_listener);
+            }
 
             let event_bus = EventBus::new(listeners);
 
             run_sequential(runner, &event_bus, &config);
 
-            test_summary_listener.print_test_summary();
+            test_summary_listener.print_test_summary(summary_to_stderr);
+            print_random_seed(config.order);
             exit_status_listener.get_exit_status(config.strict)
         },
         ExecutionMode::ParallelFeatures { event_listeners } => {
-            init_rayon();
+            init_rayon(config_overrides.num_threads);
 
             let exit_status_listener = SyncExitStatusListener::new();
             let test_summary_listener = SyncTestSummaryListener::new();
+            let formatter_listeners = create_sync_formatters(&formatters, &config);
 
-            let mut listeners: Vec<&SyncEventListener> = Vec::with_capacity(2 + event_listeners.len());
+            let mut listeners: Vec<&SyncEventListener> = Vec::with_capacity(
+                2 + event_listeners.len() + formatter_listeners.len());
             listeners.push(&exit_status_listener);
             listeners.push(&test_summary_listener);
 
             for event_listener in event_listeners {
                 listeners.push(*event_listener);
             }
+            for formatter_listener in &formatter_listeners {
+                listeners.push(&**formatter_listener);
+            }
 
             let event_bus = SyncEventBus::new(listeners);
 
             run_parallel_features(runner, &event_bus, &config);
 
-            test_summary_listener.print_test_summary();
+            test_summary_listener.print_test_summary(summary_to_stderr);
+            print_random_seed(config.order);
             exit_status_listener.get_exit_status(config.strict)
         },
         ExecutionMode::ParallelScenarios { event_listeners } => {
-            init_rayon();
+            init_rayon(config_overrides.num_threads);
 
             let exit_status_listener = SyncExitStatusListener::new();
             let test_summary_listener = SyncTestSummaryListener::new();
+            let formatter_listeners = create_sync_formatters(&formatters, &config);
 
-            let mut listeners: Vec<&SyncEventListener> = Vec::with_capacity(2 + event_listeners.len());
+            let mut listeners: Vec<&SyncEventListener> = Vec::with_capacity(
+                2 + event_listeners.len() + formatter_listeners.len());
             listeners.push(&exit_status_listener);
             listeners.push(&test_summary_listener);
 
             for event_listener in event_listeners {
                 listeners.push(*event_listener);
             }
+            for formatter_listener in &formatter_listeners {
+                listeners.push(&**formatter_listener);
+            }
 
             let event_bus = SyncEventBus::new(listeners);
 
             run_parallel_scenarios(runner, &event_bus, &config);
 
-            test_summary_listener.print_test_summary();
+            test_summary_listener.print_test_summary(summary_to_stderr);
+            print_random_seed(config.order);
             exit_status_listener.get_exit_status(config.strict)
         },
     }
 }
 
+/// Prints the seed of a random order, which reproduces the order with `random:<seed>`.
+/// It goes to stderr, so it does not break formatters writing to stdout like TAP.
+fn print_random_seed(order: Order) {
+    if let Order::Random { seed: Some(seed) } = order {
+        eprintln!("Running the cukes in random order with seed {} (reproduce with --order random:{})",
+            seed, seed);
+    }
+}
+
+/// Replaces the cuke selection of the config with the feature locations of the rerun file.
+fn select_rerun_file_locations(config: &mut Config) {
+    let rerun_file = match config.rerun_file {
+        Some(rerun_file) => rerun_file,
+        None => return,
+    };
+
+    let source = match fs::read_to_string(rerun_file) {
+        Ok(source) => source,
+        Err(err) => panic!("could not read rerun file \"{}\": {}", rerun_file.display(), err),
+    };
+    let feature_locations = match filter::parse_rerun_file(&source) {
+        Ok(feature_locations) => feature_locations,
+        Err(err) => panic!("rerun file \"{}\" is invalid: {}", rerun_file.display(), err),
+    };
+
+    // Without feature locations nothing is run, instead of all feature files.
+    config.features = feature_locations.iter()
+        .map(|feature_location| feature_location.path().to_path_buf())
+        .collect();
+    config.exclude_features = Vec::new();
+    config.feature_locations = feature_locations.iter()
+        .map(|feature_location| feature_location.to_string())
+        .collect();
+    config.tags = Vec::new();
+    config.names = Vec::new();
+}
+
+fn create_formatter(formatter: Formatter, config: &Config) -> Box<EventListener> {
+    match formatter {
+        Formatter::Pretty => Box::new(PrettyFormatter::new()),
+        Formatter::ProgressBar => Box::new(ProgressBarListener::new()),
+        Formatter::Rerun => Box::new(RerunFormatter::new(config.output_dir, config.strict)),
+        Formatter::Json => Box::new(JsonFormatter::new(config.output_dir)),
+        Formatter::JUnit => Box::new(JUnitFormatter::new(config.output_dir)),
+        Formatter::Ndjson => Box::new(NdjsonFormatter::new(config.output_dir, config.strict)),
+        Formatter::Html => Box::new(HtmlFormatter::new(config.output_dir)),
+        Formatter::Tap => Box::new(TapFormatter::new()),
+        Formatter::Usage => Box::new(UsageFormatter::new(config.output_dir)),
+    }
+}
+
+fn create_sync_formatters(formatters: &[Formatter], config: &Config) -> Vec<Box<SyncEventListener>> {
+    formatters.iter()
+        .map(|formatter| -> Box<SyncEventListener> {
+            match formatter {
+                Formatter::Pretty => unreachable!("the pretty formatter is rejected \
+                    for parallel execution modes by the config overrides"),
+                Formatter::ProgressBar => Box::new(ProgressBarListener::new()),
+                Formatter::Rerun => Box::new(RerunFormatter::new(config.output_dir, config.strict)),
+                Formatter::Json => Box::new(JsonFormatter::new(config.output_dir)),
+                Formatter::JUnit => Box::new(JUnitFormatter::new(config.output_dir)),
+                Formatter::Ndjson => Box::new(NdjsonFormatter::new(config.output_dir, config.strict)),
+                Formatter::Html => Box::new(HtmlFormatter::new(config.output_dir)),
+                Formatter::Tap => Box::new(TapFormatter::new()),
+                Formatter::Usage => Box::new(UsageFormatter::new(config.output_dir)),
+            }
+        })
+        .collect()
+}
+
 struct ParsedGherkinDocument {
     uri: String,
     source: String,
@@ -106,117 +221,190 @@ struct ParsedCuke<'d> {
     cuke: Cuke<'d>,
 }
 
+struct ParsedFeature<'d> {
+    uri: &'d str,
+    feature: &'d Feature,
+    cukes: Vec<Cuke<'d>>,
+}
+
 fn run_sequential(runner: Runner, event_bus: &EventBus, config: &Config) {
-    let parsed_gherkin_documents = parse_gherking_documents(config);
-    let parsed_cukes = parse_cukes(&parsed_gherkin_documents, event_bus);
+    runner.send_glue_loaded(event_bus);
+
+    let cuke_filter = CukeFilter::new(config);
+    let (parsed_gherkin_documents, num_skipped_cukes) = parse_gherking_documents(config, &cuke_filter);
+    let (parsed_cukes, num_filtered_out) = parse_cukes(&parsed_gherkin_documents, &cuke_filter, event_bus);
+    let num_filtered_out = num_skipped_cukes + num_filtered_out;
+    let num_cukes = parsed_cukes.len();
+    runner.check_retry_tags(parsed_cukes.iter().map(|parsed_cuke| &parsed_cuke.cuke));
+    let mut parsed_features = group_by_feature(parsed_cukes);
+    order::order_features(config.order, &mut parsed_features);
+
+    let mut global_scenario = ::glue::scenario::Scenario::new();
+    runner.run_before_all_hooks(event_bus, &mut global_scenario);
 
     event_bus.send(Event::TestRunStarted {
         time: SystemTime::now(),
-        num_cukes: parsed_cukes.len(),
+        num_cukes,
+        num_filtered_out,
     });
 
-    for parsed_cuke in parsed_cukes {
-        runner.run(&parsed_cuke.uri, parsed_cuke.cuke, event_bus)
+    for parsed_feature in parsed_features {
+        let uri = parsed_feature.uri;
+        let cukes = parsed_feature.cukes;
+
+        runner.run_feature(uri, parsed_feature.feature, event_bus, |feature_data, feature_skipped| {
+            for cuke in cukes {
+                runner.run(uri, cuke, feature_data, feature_skipped, event_bus)
+            }
+        });
     }
 
+    runner.run_after_all_hooks(event_bus, &mut global_scenario);
+
     event_bus.send(Event::TestRunFinished {
         time: SystemTime::now(),
     });
 }
 
 fn run_parallel_features(runner: Runner, event_bus: &SyncEventBus, config: &Config) {
-    let parsed_gherkin_documents = parse_gherking_documents(config);
-    let parsed_cukes = parse_cukes(&parsed_gherkin_documents, event_bus);
+    runner.send_glue_loaded(event_bus);
+
+    let cuke_filter = CukeFilter::new(config);
+    let (parsed_gherkin_documents, num_skipped_cukes) = parse_gherking_documents(config, &cuke_filter);
+    let (parsed_cukes, num_filtered_out) = parse_cukes(&parsed_gherkin_documents, &cuke_filter, event_bus);
+    let num_filtered_out = num_skipped_cukes + num_filtered_out;
+    let num_cukes = parsed_cukes.len();
+    runner.check_retry_tags(parsed_cukes.iter().map(|parsed_cuke| &parsed_cuke.cuke));
+    let mut parsed_features = group_by_feature(parsed_cukes);
+    order::order_features(config.order, &mut parsed_features);
+
+    let mut global_scenario = ::glue::scenario::Scenario::new();
+    runner.run_before_all_hooks(event_bus, &mut global_scenario);
 
     event_bus.send(Event::TestRunStarted {
         time: SystemTime::now(),
-        num_cukes: parsed_cukes.len(),
+        num_cukes,
+        num_filtered_out,
     });
 
-    let mut feature_cukes = HashMap::with_capacity(parsed_cukes.len());
-    for parsed_cuke in parsed_cukes {
-        feature_cukes.entry(parsed_cuke.uri)
-            .or_insert_with(Vec::new)
-            .push(parsed_cuke.cuke);
-    }
-    feature_cukes.shrink_to_fit();
+    parsed_features.into_par_iter().for_each(|parsed_feature| {
+        let uri = parsed_feature.uri;
+        let cukes = parsed_feature.cukes;
 
-    feature_cukes.into_par_iter().for_each(|(uri, cukes)| {
-        for cuke in cukes {
-            runner.run(uri, cuke, event_bus)
-        }
+        runner.run_feature(uri, parsed_feature.feature, event_bus, |feature_data, feature_skipped| {
+            for cuke in cukes {
+                runner.run(uri, cuke, feature_data, feature_skipped, event_bus)
+            }
+        });
     });
 
+    runner.run_after_all_hooks(event_bus, &mut global_scenario);
+
     event_bus.send(Event::TestRunFinished {
         time: SystemTime::now(),
     });
 }
 
 fn run_parallel_scenarios(runner: Runner, event_bus: &SyncEventBus, config: &Config) {
-    let parsed_gherkin_documents = parse_gherking_documents(config);
-    let parsed_cukes = parse_cukes(&parsed_gherkin_documents, event_bus);
+    runner.send_glue_loaded(event_bus);
+
+    let cuke_filter = CukeFilter::new(config);
+    let (parsed_gherkin_documents, num_skipped_cukes) = parse_gherking_documents(config, &cuke_filter);
+    let (parsed_cukes, num_filtered_out) = parse_cukes(&parsed_gherkin_documents, &cuke_filter, event_bus);
+    let num_filtered_out = num_skipped_cukes + num_filtered_out;
+    let num_cukes = parsed_cukes.len();
+    runner.check_retry_tags(parsed_cukes.iter().map(|parsed_cuke| &parsed_cuke.cuke));
+    let mut parsed_features = group_by_feature(parsed_cukes);
+    order::order_features(config.order, &mut parsed_features);
+
+    let mut global_scenario = ::glue::scenario::Scenario::new();
+    runner.run_before_all_hooks(event_bus, &mut global_scenario);
 
     event_bus.send(Event::TestRunStarted {
         time: SystemTime::now(),
-        num_cukes: parsed_cukes.len(),
+        num_cukes,
+        num_filtered_out,
     });
 
-    parsed_cukes.into_par_iter().for_each(|parsed_cuke| {
-        runner.run(parsed_cuke.uri, parsed_cuke.cuke, event_bus);
+    // The scenarios of a feature only start after its `BeforeFeature` hooks are finished
+    // and its `AfterFeature` hooks only start after all of its scenarios are finished.
+    parsed_features.into_par_iter().for_each(|parsed_feature| {
+        let uri = parsed_feature.uri;
+        let cukes = parsed_feature.cukes;
+
+        runner.run_feature(uri, parsed_feature.feature, event_bus, |feature_data, feature_skipped| {
+            cukes.into_par_iter().for_each(|cuke| {
+                runner.run(uri, cuke, feature_data, feature_skipped, event_bus);
+            });
+        });
     });
 
+    runner.run_after_all_hooks(event_bus, &mut global_scenario);
+
     event_bus.send(Event::TestRunFinished {
         time: SystemTime::now(),
     });
 }
 
-fn init_rayon() {
+fn init_rayon(num_threads: Option<usize>) {
     rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads.unwrap_or(0))
         .thread_name(|thread_index| format!("rayon-{}", thread_index))
         .build_global()
         .expect("Failed to build global rayon thread pool");
 }
 
-fn parse_gherking_documents(config: &Config) -> Vec<ParsedGherkinDocument> {
-    let walk_dir = WalkDir::new(config.features_dir)
-        .follow_links(true);
+/// Parses the feature files and returns the ones that can contain cukes selected by the feature
+/// locations, together with the number of cukes of the skipped ones (which are all filtered out).
+fn parse_gherking_documents(config: &Config, cuke_filter: &CukeFilter) -> (Vec<ParsedGherkinDocument>, usize) {
+    let feature_files = match feature_files::find(&config.features, &config.exclude_features) {
+        Ok(feature_files) => feature_files,
+        Err(err) => panic!("could not find feature files: {}", err),
+    };
 
     let mut gherkin_parser = gherkin::Parser::default();
+    let mut gherkin_compiler = gherkin::cuke::Compiler::default();
+    let mut parsed_gherkin_documents = Vec::new();
+    let mut num_skipped_cukes = 0;
+
+    for path in feature_files {
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(err) => panic!("could not read feature file \"{}\": {}", &path.display(), err),
+        };
+
+        let gherkin_document = match gherkin_parser.parse_str(&source) {
+            Ok(document) => document,
+            Err(err) => panic!("could not parse feature file \"{}\": {}", &path.display(), err),
+        };
+
+        if !cuke_filter.matches_feature_file(&path) {
+            num_skipped_cukes += gherkin_compiler.compile(&gherkin_document).len();
+            continue;
+        }
 
-    walk_dir.into_iter()
-        .map(Result::unwrap)
-        .filter(|entry| entry.file_name().to_string_lossy().ends_with(".feature"))
-        .map(DirEntry::into_path)
-        .map(|path| {
-            let source = match fs::read_to_string(&path) {
-                Ok(source) => source,
-                Err(err) => panic!("could not read feature file \"{}\": {}", &path.display(), err),
-            };
-
-            let gherkin_document = match gherkin_parser.parse_str(&source) {
-                Ok(document) => document,
-                Err(err) => panic!("could not parse feature file \"{}\": {}", &path.display(), err),
-            };
+        let uri = path.display().to_string();
 
-            let uri = path.display().to_string();
+        parsed_gherkin_documents.push(ParsedGherkinDocument {
+            uri,
+            source,
+            document: gherkin_document,
+        });
+    }
 
-            ParsedGherkinDocument {
-                uri,
-                source,
-                document: gherkin_document,
-            }
-        })
-        .collect::<Vec<ParsedGherkinDocument>>()
+    (parsed_gherkin_documents, num_skipped_cukes)
 }
 
 fn parse_cukes<'d>(
     parsed_gherkin_documents: &'d [ParsedGherkinDocument],
+    cuke_filter: &CukeFilter,
     event_publisher: &EventPublisher,
-) -> Vec<ParsedCuke<'d>>
+) -> (Vec<ParsedCuke<'d>>, usize)
 {
     let mut gherkin_compiler = gherkin::cuke::Compiler::default();
+    let mut num_filtered_out = 0;
 
-    parsed_gherkin_documents.iter()
+    let parsed_cukes = parsed_gherkin_documents.iter()
         .flat_map(|parsed_gherkin_document| {
             let feature = match parsed_gherkin_document.document.feature {
                 Some(ref feature) => feature,
@@ -233,12 +421,41 @@ fn parse_cukes<'d>(
                 cukes: &cukes,
             });
 
-            cukes.into_iter()
+            let num_cukes = cukes.len();
+            let parsed_cukes = cukes.into_iter()
+                .filter(|cuke| cuke_filter.matches(&parsed_gherkin_document.uri, cuke))
                 .map(|cuke| ParsedCuke {
                     uri: &parsed_gherkin_document.uri,
                     cuke,
                 })
-                .collect::<Vec<ParsedCuke>>()
+                .collect::<Vec<ParsedCuke>>();
+            num_filtered_out += num_cukes - parsed_cukes.len();
+
+            parsed_cukes
         })
-        .collect::<Vec<ParsedCuke>>()
+        .collect::<Vec<ParsedCuke>>();
+
+    (parsed_cukes, num_filtered_out)
+}
+
+/// Groups the cukes by their feature file, keeping the order of the feature files and cukes.
+fn group_by_feature(parsed_cukes: Vec<ParsedCuke>) -> Vec<ParsedFeature> {
+    let mut parsed_features: Vec<ParsedFeature> = Vec::new();
+
+    for parsed_cuke in parsed_cukes {
+        let same_feature = parsed_features.last()
+            .map_or(false, |parsed_feature| parsed_feature.uri == parsed_cuke.uri);
+
+        if !same_feature {
+            parsed_features.push(ParsedFeature {
+                uri: parsed_cuke.uri,
+                feature: parsed_cuke.cuke.feature,
+                cukes: Vec::new(),
+            });
+        }
+
+        parsed_features.last_mut().unwrap().cukes.push(parsed_cuke.cuke);
+    }
+
+    parsed_features
 }
@@ -0,0 +1,112 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use Order;
+use super::ParsedFeature;
+
+/// Generates a seed for a random order without one, so the order can be reproduced.
+pub fn with_seed(order: Order) -> Order {
+    match order {
+        Order::Random { seed: None } => Order::Random { seed: Some(generate_seed()) },
+        order => order,
+    }
+}
+
+/// Orders the features and the cukes of each feature.
+pub fn order_features(order: Order, parsed_features: &mut [ParsedFeature]) {
+    match order {
+        Order::Defined => {},
+        Order::Lexical => {
+            parsed_features.sort_by_key(|parsed_feature| parsed_feature.uri);
+            for parsed_feature in parsed_features {
+                parsed_feature.cukes.sort_by_key(|cuke| cuke.locations.iter()
+                    .map(|location| location.line)
+                    .collect::<Vec<u32>>());
+            }
+        },
+        Order::Reverse => {
+            parsed_features.reverse();
+            for parsed_feature in parsed_features {
+                parsed_feature.cukes.reverse();
+            }
+        },
+        Order::Random { seed } => {
+            let mut rng = SplitMix64::new(seed.unwrap_or_else(generate_seed));
+            shuffle(parsed_features, &mut rng);
+            for parsed_feature in parsed_features {
+                shuffle(&mut parsed_feature.cukes, &mut rng);
+            }
+        },
+    }
+}
+
+fn generate_seed() -> u64 {
+    let duration = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    duration.as_secs() ^ u64::from(duration.subsec_nanos()).rotate_left(32)
+}
+
+/// Shuffles the items with the Fisher-Yates algorithm.
+fn shuffle<T>(items: &mut [T], rng: &mut SplitMix64) {
+    for i in (1..items.len()).rev() {
+        let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// A small pseudo random number generator, so the order of a seed is the same
+/// on every platform and with every version of the dependencies.
+#[derive(Debug)]
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 {
+            state: seed,
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shuffled(seed: u64) -> Vec<u32> {
+        let mut items = (0..20).collect::<Vec<u32>>();
+        shuffle(&mut items, &mut SplitMix64::new(seed));
+        items
+    }
+
+    #[test]
+    fn same_seed_gives_same_order() {
+        assert_eq!(shuffled(42), shuffled(42));
+        assert_ne!(shuffled(42), shuffled(43));
+    }
+
+    #[test]
+    fn shuffle_keeps_all_items() {
+        let mut items = shuffled(7);
+        assert_ne!(items, (0..20).collect::<Vec<u32>>());
+
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn random_order_without_seed_gets_one() {
+        match with_seed(Order::Random { seed: None }) {
+            Order::Random { seed: Some(_) } => {},
+            order => panic!("unexpected order {:?}", order),
+        }
+        assert_eq!(with_seed(Order::Random { seed: Some(3) }), Order::Random { seed: Some(3) });
+        assert_eq!(with_seed(Order::Lexical), Order::Lexical);
+    }
+}
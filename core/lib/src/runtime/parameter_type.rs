@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use glue::StaticGlueDefinitions;
+use glue::parameter_type::StaticParameterTypeDef;
+use glue::step::cucumber_expression;
+
+/// The user defined parameter types that can be referenced in cucumber expressions.
+#[derive(Debug)]
+pub struct ParameterTypeRegistry {
+    parameter_types_by_name: HashMap<&'static str, &'static StaticParameterTypeDef>,
+}
+
+impl From<&[StaticGlueDefinitions]> for ParameterTypeRegistry {
+    fn from(static_glue_definitions: &[StaticGlueDefinitions]) -> ParameterTypeRegistry {
+        let mut parameter_types_by_name = HashMap::new();
+
+        static_glue_definitions.iter()
+            .flat_map(|glue| glue.parameter_types.iter())
+            .for_each(|&parameter_type| {
+                if cucumber_expression::builtin_parameter_type_regex(parameter_type.name).is_some() {
+                    panic!("parameter type \"{}\" conflicts with the built-in parameter type of the same name:
+  defined: {}", parameter_type.name, parameter_type.location)
+                }
+
+                if let Some(prev) = parameter_types_by_name.insert(parameter_type.name, parameter_type) {
+                    panic!("duplicate parameter type \"{}\":
+   first: {}
+  second: {}", prev.name, prev.location, parameter_type.location)
+                }
+            });
+
+        ParameterTypeRegistry {
+            parameter_types_by_name,
+        }
+    }
+}
+
+impl ParameterTypeRegistry {
+    /// Returns the regex of the built-in or user defined parameter type with the given name.
+    pub fn lookup_regex(&self, name: &str) -> Option<String> {
+        cucumber_expression::builtin_parameter_type_regex(name)
+            .or_else(|| self.parameter_types_by_name.get(name)
+                .map(|parameter_type| parameter_type.regex))
+            .map(str::to_owned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glue::CodeLocation;
+
+    use super::*;
+
+    static CURRENCY: StaticParameterTypeDef = StaticParameterTypeDef {
+        name: "currency",
+        regex: "EUR|USD",
+        location: CodeLocation { file_path: "steps/money.rs", line_number: 3 },
+    };
+
+    static OTHER_CURRENCY: StaticParameterTypeDef = StaticParameterTypeDef {
+        name: "currency",
+        regex: "[A-Z]{3}",
+        location: CodeLocation { file_path: "steps/exchange.rs", line_number: 8 },
+    };
+
+    static INT: StaticParameterTypeDef = StaticParameterTypeDef {
+        name: "int",
+        regex: "[0-9]+",
+        location: CodeLocation { file_path: "steps/numbers.rs", line_number: 5 },
+    };
+
+    static CURRENCIES: &[&StaticParameterTypeDef] = &[&CURRENCY];
+    static OTHER_CURRENCIES: &[&StaticParameterTypeDef] = &[&OTHER_CURRENCY];
+    static INTS: &[&StaticParameterTypeDef] = &[&INT];
+
+    fn glue(parameter_types: &'static [&'static StaticParameterTypeDef]) -> StaticGlueDefinitions {
+        StaticGlueDefinitions {
+            parameter_types,
+            before_all_hooks: &[],
+            before_feature_hooks: &[],
+            before_scenario_hooks: &[],
+            before_step_hooks: &[],
+            steps: &[],
+            after_step_hooks: &[],
+            after_scenario_hooks: &[],
+            after_feature_hooks: &[],
+            after_all_hooks: &[],
+        }
+    }
+
+    #[test]
+    fn lookup_regex_finds_built_in_and_user_defined_parameter_types() {
+        let registry = ParameterTypeRegistry::from(&[glue(CURRENCIES)][..]);
+
+        assert_eq!(registry.lookup_regex("int"), Some(r"-?\d+".to_owned()));
+        assert_eq!(registry.lookup_regex("currency"), Some("EUR|USD".to_owned()));
+        assert_eq!(registry.lookup_regex("intt"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate parameter type \"currency\"")]
+    fn duplicate_parameter_types_are_rejected() {
+        ParameterTypeRegistry::from(&[glue(CURRENCIES), glue(OTHER_CURRENCIES)][..]);
+    }
+
+    #[test]
+    #[should_panic(expected = "parameter type \"int\" conflicts with the built-in parameter type")]
+    fn parameter_types_must_not_shadow_built_in_parameter_types() {
+        ParameterTypeRegistry::from(&[glue(INTS)][..]);
+    }
+}
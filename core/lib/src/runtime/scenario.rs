@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use gherkin::cuke::{Cuke, Tag};
@@ -7,6 +8,7 @@ use runner::EventPublisher;
 use api::{TestResult, TestResultStatus};
 use api::event::Event;
 use glue;
+use glue::scenario::FeatureData;
 
 #[derive(Debug)]
 pub struct Scenario<'a, 'b> {
@@ -21,7 +23,9 @@ pub struct Scenario<'a, 'b> {
 }
 
 impl<'a, 'b> Scenario<'a, 'b> {
-    pub fn new(uri: &'a str, cuke: &'a Cuke, event_publisher: &'b EventPublisher) -> Scenario<'a, 'b> {
+    pub fn new(uri: &'a str, cuke: &'a Cuke, feature_data: Arc<FeatureData>,
+        event_publisher: &'b EventPublisher) -> Scenario<'a, 'b>
+    {
         let test_results = Vec::new();
         let tags = &cuke.tags;
         let name = &cuke.name;
@@ -39,7 +43,7 @@ impl<'a, 'b> Scenario<'a, 'b> {
             id,
             lines,
             event_publisher,
-            glue_scenario: glue::scenario::Scenario::new(),
+            glue_scenario: glue::scenario::Scenario::with_feature_data(feature_data),
         }
     }
 
@@ -68,6 +72,8 @@ impl<'a, 'b> Scenario<'a, 'b> {
     pub fn embed(&self, data: &[u8], mime_type: String) {
         self.event_publisher.send(Event::Embed {
             time: SystemTime::now(),
+            uri: self.uri,
+            line: self.lines[0],
             data,
             mime_type,
         });
@@ -76,6 +82,8 @@ impl<'a, 'b> Scenario<'a, 'b> {
     pub fn write(&self, text: &str) {
         self.event_publisher.send(Event::Write {
             time: SystemTime::now(),
+            uri: self.uri,
+            line: self.lines[0],
             text,
         });
     }
@@ -0,0 +1,165 @@
+use regex::Regex;
+
+use gherkin::cuke;
+
+use glue::step::StepKeyword;
+
+const CUCUMBER_EXPRESSION_SPECIAL_CHARS: &[char] = &['(', ')', '{', '}', '/', '\\'];
+
+/// The kind of the DocString or DataTable argument of a step.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum StepArgumentKind {
+    DocString,
+    DataTable,
+}
+
+/// Resolves the keyword of a step for the step definition attribute.
+///
+/// Conjunctions like "And" and "But" (as well as "*") take the keyword of the previous step.
+pub fn resolve_keyword(step_keyword: &str, previous_keyword: StepKeyword) -> StepKeyword {
+    match step_keyword.trim().parse() {
+        Ok(StepKeyword::Star) | Err(_) => previous_keyword,
+        Ok(keyword) => keyword,
+    }
+}
+
+/// Generates a step definition snippet that matches the given (undefined) step.
+pub fn generate(keyword: StepKeyword, step: &cuke::Step) -> String {
+    let argument_kind = step.argument.as_ref().map(|argument| match argument {
+        cuke::Argument::String(_) => StepArgumentKind::DocString,
+        cuke::Argument::Table(_) => StepArgumentKind::DataTable,
+    });
+
+    generate_snippet(keyword, &step.text, argument_kind)
+}
+
+fn generate_snippet(keyword: StepKeyword, step_text: &str, argument_kind: Option<StepArgumentKind>)
+    -> String
+{
+    let parameter_regex = Regex::new(r#""[^"]*"|'[^']*'|-?\b\d+\.\d+\b|-?\b\d+\b"#).unwrap();
+
+    let mut expression = String::new();
+    let mut function_name_text = String::new();
+    let mut parameters = vec![String::from("scenario: &mut Scenario")];
+    let mut text_start = 0;
+
+    for mat in parameter_regex.find_iter(step_text) {
+        let text = &step_text[text_start..mat.start()];
+        expression.push_str(&escape_cucumber_expression(text));
+        function_name_text.push_str(text);
+        text_start = mat.end();
+
+        let (parameter_type, rust_type) = match mat.as_str().chars().next() {
+            Some('"') | Some('\'') => ("{string}", "&str"),
+            _ if mat.as_str().contains('.') => ("{float}", "f64"),
+            _ => ("{int}", "i32"),
+        };
+        expression.push_str(parameter_type);
+        parameters.push(format!("arg{}: {}", parameters.len(), rust_type));
+    }
+
+    let text = &step_text[text_start..];
+    expression.push_str(&escape_cucumber_expression(text));
+    function_name_text.push_str(text);
+
+    match argument_kind {
+        Some(StepArgumentKind::DocString) => parameters.push(String::from("doc_string: &str")),
+        Some(StepArgumentKind::DataTable) => parameters.push(String::from("data_table: &DataTable")),
+        None => {},
+    }
+
+    format!("#[{}(\"{}\")]\npub fn {}({}) {{\n    \
+            // Write code here that turns the phrase above into concrete actions\n    \
+            pending!();\n\
+        }}\n",
+        keyword.as_str().to_lowercase(),
+        escape_string_literal(&expression),
+        function_name(&function_name_text),
+        parameters.join(", "))
+}
+
+fn escape_cucumber_expression(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if CUCUMBER_EXPRESSION_SPECIAL_CHARS.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+fn escape_string_literal(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn function_name(text: &str) -> String {
+    let words = text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<String>>();
+
+    let name = words.join("_");
+    match name.chars().next() {
+        None => String::from("step"),
+        Some(c) if c.is_numeric() => format!("step_{}", name),
+        Some(_) => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_become_typed_parameters() {
+        let snippet = generate_snippet(StepKeyword::When, "I add 4 and 5.5", None);
+
+        assert_eq!(snippet, "\
+#[when(\"I add {int} and {float}\")]
+pub fn i_add_and(scenario: &mut Scenario, arg1: i32, arg2: f64) {
+    // Write code here that turns the phrase above into concrete actions
+    pending!();
+}
+");
+    }
+
+    #[test]
+    fn quoted_strings_become_string_parameters() {
+        let snippet = generate_snippet(StepKeyword::Given, "a user named \"Bob\" with role 'admin'", None);
+
+        assert!(snippet.starts_with("#[given(\"a user named {string} with role {string}\")]\n\
+            pub fn a_user_named_with_role(scenario: &mut Scenario, arg1: &str, arg2: &str) {"));
+    }
+
+    #[test]
+    fn doc_string_and_data_table_arguments_are_added() {
+        let doc_string_snippet = generate_snippet(StepKeyword::Given, "the text:",
+            Some(StepArgumentKind::DocString));
+        let data_table_snippet = generate_snippet(StepKeyword::Given, "the previous entries:",
+            Some(StepArgumentKind::DataTable));
+
+        assert!(doc_string_snippet.contains("pub fn the_text(scenario: &mut Scenario, doc_string: &str)"));
+        assert!(data_table_snippet.contains(
+            "pub fn the_previous_entries(scenario: &mut Scenario, data_table: &DataTable)"));
+    }
+
+    #[test]
+    fn special_characters_are_escaped() {
+        let snippet = generate_snippet(StepKeyword::Then, "the result is (roughly) 1/2", None);
+
+        assert!(snippet.starts_with(
+            "#[then(\"the result is \\\\(roughly\\\\) {int}\\\\/{int}\")]\n\
+            pub fn the_result_is_roughly(scenario: &mut Scenario, arg1: i32, arg2: i32) {"));
+    }
+
+    #[test]
+    fn conjunctions_take_the_previous_keyword() {
+        assert_eq!(resolve_keyword("When ", StepKeyword::Given), StepKeyword::When);
+        assert_eq!(resolve_keyword("And ", StepKeyword::When), StepKeyword::When);
+        assert_eq!(resolve_keyword("But ", StepKeyword::Then), StepKeyword::Then);
+        assert_eq!(resolve_keyword("* ", StepKeyword::Given), StepKeyword::Given);
+    }
+}
@@ -9,6 +9,7 @@ use glue::step::{StaticStepDef, StepFn};
 use glue::step::argument::{StepArgument, DocString, DataTable};
 use runtime::Scenario;
 
+use super::parameter_type::ParameterTypeRegistry;
 use super::step_expression::StepExpression;
 
 #[derive(Clone)]
@@ -32,18 +33,18 @@ impl fmt::Debug for StepDefinition {
     }
 }
 
-impl From<&&StaticStepDef> for StepDefinition {
-    fn from(static_step_def: &&StaticStepDef) -> Self {
+impl StepDefinition {
+    pub fn new(static_step_def: &StaticStepDef, parameter_type_registry: &ParameterTypeRegistry)
+        -> StepDefinition
+    {
         StepDefinition {
-            expression: StepExpression::from_regex(static_step_def.expression),
+            expression: StepExpression::new(static_step_def.expression, parameter_type_registry),
             parameter_infos: Vec::new(),
             step_fn: static_step_def.step_fn,
             location: static_step_def.location,
         }
     }
-}
 
-impl StepDefinition {
     /// Returns the list of arguments for this step definition.
     ///
     /// Returns `None` if the step definition doesn't match at all.
@@ -92,7 +93,7 @@ impl StepDefinition {
     }
 
     /// The step definition pattern for error reporting only.
-    pub fn get_pattern(&self) -> &String {
-        unimplemented!();
+    pub fn get_pattern(&self) -> &str {
+        self.expression.as_str()
     }
 }
@@ -51,7 +51,7 @@ impl<'s> StepDefinitionMatch<'s> {
         }
     }
 
-    pub fn get_pattern(&self) -> Option<&String> {
+    pub fn get_pattern(&self) -> Option<&str> {
         match self {
             StepDefinitionMatch::Hook(hook) => hook.get_pattern(),
             StepDefinitionMatch::Cuke(cuke_step) => cuke_step.get_pattern(),
@@ -83,7 +83,7 @@ impl<'s> HookDefinitionMatch<'s> {
     }
 
     pub fn run_step(&self, scenario: &mut Scenario) -> Result<()> {
-        self.hook_definition.execute(scenario)
+        self.hook_definition.execute(&mut scenario.glue_scenario)
     }
 
     pub fn dry_run_step(&self, _scenario: &mut Scenario) -> Result<()> {
@@ -94,7 +94,7 @@ impl<'s> HookDefinitionMatch<'s> {
         Some(&self.hook_definition.get_location())
     }
 
-    pub fn get_pattern(&self) -> Option<&String> {
+    pub fn get_pattern(&self) -> Option<&str> {
         None
     }
 
@@ -129,7 +129,7 @@ impl<'s> CukeStepDefinitionMatch<'s> {
         Some(&self.step_definition.get_location())
     }
 
-    fn get_pattern(&self) -> Option<&String> {
+    fn get_pattern(&self) -> Option<&str> {
         Some(self.step_definition.get_pattern())
     }
 
@@ -142,6 +142,7 @@ impl<'s> CukeStepDefinitionMatch<'s> {
 pub struct AmbiguousCukeStepDefinitionMatch<'s> {
     pub feature_path: String,
     pub step: &'s cuke::Step<'s>,
+    pub step_definitions: Vec<StepDefinition>,
     pub arguments: Vec<StepArgument<'s>>,
 }
 
@@ -151,7 +152,16 @@ impl<'s> AmbiguousCukeStepDefinitionMatch<'s> {
     }
 
     fn run_step(&self, _scenario: &mut Scenario) -> Result<()> {
-        unimplemented!("AmbiguousPickleStepDefinitionMatch::run_step");
+        let step_definitions = self.step_definitions.iter()
+            .map(|step_definition| {
+                (step_definition.get_pattern().to_owned(), *step_definition.get_location())
+            })
+            .collect();
+
+        Err(Error::AmbiguousStepDefinitions {
+            step_text: self.step.text.to_string(),
+            step_definitions,
+        })
     }
 
     fn dry_run_step(&self, scenario: &mut Scenario) -> Result<()> {
@@ -162,7 +172,7 @@ impl<'s> AmbiguousCukeStepDefinitionMatch<'s> {
         None
     }
 
-    fn get_pattern(&self) -> Option<&String> {
+    fn get_pattern(&self) -> Option<&str> {
         None
     }
 
@@ -194,7 +204,7 @@ impl<'s> UndefinedCukeStepDefinitionMatch<'s> {
         None
     }
 
-    fn get_pattern(&self) -> Option<&String> {
+    fn get_pattern(&self) -> Option<&str> {
         None
     }
 
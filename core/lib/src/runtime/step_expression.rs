@@ -1,29 +1,92 @@
-use regex::Regex;
+use regex::{Captures, Regex};
 
 use glue::step::argument::{StepArgument, Expression};
+use glue::step::cucumber_expression::{self, CucumberExpression, ParameterGroup};
+
+use super::parameter_type::ParameterTypeRegistry;
 
 #[derive(Debug, Clone)]
 pub struct StepExpression {
+    /// The expression as written in the step attribute.
+    pub source: String,
     pub regex: Regex,
+    /// The capture groups of the parameters if this is a cucumber expression,
+    /// `None` for raw regexes where every matched capture group is an argument.
+    parameter_groups: Option<Vec<ParameterGroup>>,
 }
 
 impl StepExpression {
+    /// Creates a step expression from either a regex or a cucumber expression.
+    ///
+    /// Expressions anchored with `^` or `$` are treated as regexes.
+    pub fn new(expression: &str, parameter_type_registry: &ParameterTypeRegistry) -> StepExpression {
+        if cucumber_expression::is_regex(expression) {
+            StepExpression::from_regex(expression)
+        } else {
+            StepExpression::from_cucumber_expression(expression, parameter_type_registry)
+        }
+    }
+
     pub fn from_regex(regex: &str) -> StepExpression {
         StepExpression {
+            source: regex.to_owned(),
             regex: Regex::new(regex).unwrap(),
+            parameter_groups: None,
+        }
+    }
+
+    pub fn from_cucumber_expression(expression: &str, parameter_type_registry: &ParameterTypeRegistry)
+        -> StepExpression
+    {
+        let compiled = CucumberExpression::parse(expression)
+            .and_then(|cucumber_expression| cucumber_expression.compile(|name| {
+                parameter_type_registry.lookup_regex(name)
+            }))
+            .unwrap_or_else(|err| panic!("invalid cucumber expression \"{}\": {}", expression, err));
+
+        StepExpression {
+            source: expression.to_owned(),
+            regex: Regex::new(&compiled.regex).unwrap(),
+            parameter_groups: Some(compiled.parameter_groups),
         }
     }
 
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
     pub fn matched_arguments<'s>(&'s self, text: &'s str) -> Option<Vec<StepArgument<'s>>> {
         let caps = self.regex.captures(text)?;
 
-        let matched_arguments = caps.iter()
-            .skip(1) // The first match always corresponds to the overall match of the regex.
-            .filter_map(|opt_mat| {
-                opt_mat.map(|mat| StepArgument::Expression(Expression::from(mat)))
-            })
-            .collect::<Vec<StepArgument>>();
+        let matched_arguments = match self.parameter_groups {
+            Some(ref parameter_groups) => parameter_groups.iter()
+                .filter_map(|parameter_group| parameter_argument(&caps, parameter_group))
+                .collect::<Vec<StepArgument>>(),
+            None => caps.iter()
+                .skip(1) // The first match always corresponds to the overall match of the regex.
+                .filter_map(|opt_mat| {
+                    opt_mat.map(|mat| StepArgument::Expression(Expression::from(mat)))
+                })
+                .collect::<Vec<StepArgument>>(),
+        };
 
         Some(matched_arguments)
     }
 }
+
+/// Returns the argument of a cucumber expression parameter.
+///
+/// Parameter types with capture groups (like `{string}`) use the first matching
+/// inner group as value, all others use the group enclosing the whole parameter.
+fn parameter_argument<'s>(caps: &Captures<'s>, parameter_group: &ParameterGroup)
+    -> Option<StepArgument<'s>>
+{
+    let first_inner_group = parameter_group.index + 1;
+    let last_inner_group = parameter_group.index + parameter_group.inner_groups;
+
+    (first_inner_group..=last_inner_group)
+        .filter_map(|index| caps.get(index))
+        .next()
+        .or_else(|| caps.get(parameter_group.index))
+        .map(|mat| StepArgument::Expression(Expression::from(mat)))
+}
@@ -1,11 +1,14 @@
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use gherkin::cuke::{Cuke, Tag};
 
 use api::{self, TestResult, TestResultStatus};
 use api::event::Event;
+use glue::scenario::FeatureData;
+use glue::step::StepKeyword;
 use runner::{EventPublisher, CukeStepTestStep, HookTestStep};
-use runtime;
+use runtime::{self, snippet, StepDefinitionMatch};
 
 #[derive(Debug)]
 pub struct TestCase<'c> {
@@ -14,7 +17,17 @@ pub struct TestCase<'c> {
     pub test_steps: Vec<CukeStepTestStep<'c>>,
     pub before_hooks: Vec<HookTestStep<'c>>,
     pub after_hooks: Vec<HookTestStep<'c>>,
-    pub dry_run: bool,
+    pub feature_data: Arc<FeatureData>,
+    /// Whether all hooks and steps are skipped (in a dry run or after a failure in fail fast mode).
+    pub skip: bool,
+    /// Whether the test case is not run at all (after a failure in fail fast mode,
+    /// or a `BeforeAll` or `BeforeFeature` hook that did not pass),
+    /// so its hooks and steps are reported as skipped without checking their step definitions.
+    pub not_run: bool,
+    /// The attempt of running the cuke, starting at 0 (see `Config::retry`).
+    pub attempt: u32,
+    /// The number of times a failed cuke is run again.
+    pub retries: u32,
 }
 
 impl<'s> api::TestCase for TestCase<'s> {
@@ -65,7 +78,9 @@ impl<'s> api::TestCase for TestCase<'s> {
     }
 }
 
-pub fn run<EP: EventPublisher>(test_case: TestCase, event_publisher: &EP) {
+/// Runs the test case and returns its status and whether it will be retried
+/// (if it failed and retries are left).
+pub fn run<EP: EventPublisher>(test_case: TestCase, event_publisher: &EP) -> (TestResultStatus, bool) {
     let start_time = SystemTime::now();
     event_publisher.send(Event::TestCaseStarted {
         time: start_time,
@@ -74,25 +89,48 @@ pub fn run<EP: EventPublisher>(test_case: TestCase, event_publisher: &EP) {
         background: test_case.cuke.background,
         scenario_definition: &test_case.cuke.scenario_definition,
         test_case: &test_case as &api::TestCase,
+        attempt: test_case.attempt,
     });
 
-    let mut skip_next_step = test_case.dry_run;
-    let mut scenario = runtime::Scenario::new(test_case.uri, &test_case.cuke, event_publisher);
+    let mut skip_next_step = test_case.skip;
+    let mut scenario = runtime::Scenario::new(test_case.uri, &test_case.cuke,
+        test_case.feature_data.clone(), event_publisher);
 
     for before_hook in &test_case.before_hooks {
-        let hook_result = before_hook.run(event_publisher, &test_case, &mut scenario, test_case.dry_run);
+        let hook_result = before_hook.run(event_publisher, &test_case, &mut scenario, test_case.skip);
         skip_next_step = skip_next_step || !hook_result.status.eq(&TestResultStatus::Passed);
         scenario.add_test_result(hook_result);
     }
 
+    let mut previous_keyword = StepKeyword::Given;
     for step in &test_case.test_steps {
+        let cuke_step = step.step_definition_match.get_step();
+        let keyword = snippet::resolve_keyword(&cuke_step.keyword, previous_keyword);
+        previous_keyword = keyword;
+
         let step_result = step.run(event_publisher, &test_case, &mut scenario, skip_next_step);
         skip_next_step = skip_next_step || !step_result.status.eq(&TestResultStatus::Passed);
         scenario.add_test_result(step_result);
+
+        // Test cases that are not run do not suggest snippets for their undefined steps.
+        if test_case.not_run {
+            continue;
+        }
+        if let StepDefinitionMatch::Undefined(_) = step.step_definition_match {
+            let snippets = [snippet::generate(keyword, cuke_step)];
+            event_publisher.send(Event::SnippetsSuggested {
+                time: SystemTime::now(),
+                uri: test_case.uri,
+                step_line: cuke_step.locations.last()
+                    .map(|location| location.line)
+                    .unwrap_or(0),
+                snippets: &snippets,
+            });
+        }
     }
 
     for after_hook in &test_case.after_hooks {
-        let hook_result = after_hook.run(event_publisher, &test_case, &mut scenario, test_case.dry_run);
+        let hook_result = after_hook.run(event_publisher, &test_case, &mut scenario, test_case.skip);
         scenario.add_test_result(hook_result);
     }
 
@@ -106,6 +144,8 @@ pub fn run<EP: EventPublisher>(test_case: TestCase, event_publisher: &EP) {
         duration: Some(duration),
         error: scenario.into_error(),
     };
+    let will_be_retried = test_result.status == TestResultStatus::Failed
+        && test_case.attempt < test_case.retries;
     event_publisher.send(Event::TestCaseFinished {
         time: stop_time,
         uri: test_case.uri,
@@ -114,5 +154,9 @@ pub fn run<EP: EventPublisher>(test_case: TestCase, event_publisher: &EP) {
         scenario_definition: &test_case.cuke.scenario_definition,
         result: &test_result,
         test_case: &test_case as &api::TestCase,
+        attempt: test_case.attempt,
+        will_be_retried,
     });
+
+    (test_result.status, will_be_retried)
 }
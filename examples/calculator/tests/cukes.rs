@@ -5,22 +5,28 @@ extern crate calculator;
 extern crate cuke_runner;
 
 use std::path::PathBuf;
-use cuke_runner::{Config, ExecutionMode, Glue};
+use cuke_runner::{Config, ExecutionMode, Glue, Order};
 use cuke_runner::event_listener::PrettyFormatter;
 
 mod steps;
 
-#[test]
-fn test_cucumber_features_sequential() {
+fn main() {
     let glue = glue![steps];
 
     let config = Config {
-        features_dir: &[env!("CARGO_MANIFEST_DIR"), "tests", "features"].iter().collect::<PathBuf>(),
+        features: vec![[env!("CARGO_MANIFEST_DIR"), "tests", "features"].iter().collect::<PathBuf>()],
+        exclude_features: vec![],
         output_dir: &[env!("CARGO_MANIFEST_DIR"), "target", "cucumber"].iter().collect::<PathBuf>(),
         strict: true,
         colored_output: true,
         dry_run: false,
+        fail_fast: false,
+        retry: 0,
         tags: vec![],
+        feature_locations: vec![],
+        names: vec![],
+        rerun_file: None,
+        order: Order::Defined,
         execution_mode: ExecutionMode::Sequential {
             event_listeners: &[
                 &PrettyFormatter::new(),
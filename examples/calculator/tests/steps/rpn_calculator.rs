@@ -37,10 +37,6 @@ pub fn init(scenario: &mut Scenario) {
 // #[after_scenario(order, tags)]
 // #[before_step(order, tags)]
 // #[after_step(order, tags)]
-// // hook that should be executed before the tests are run (one-only global setup hook)
-// #[before_all(order)]
-// // reverse of #before_all
-// #[after_all(order)]
 // // not sure about this one yet...
 // // #[after_configuration] taking configuration as function argument
 
@@ -49,19 +45,19 @@ pub fn reset_calculator(calc: &mut Calc) {
     calc.reset();
 }
 
-#[when("I add (\\d+) and (\\d+)")]
+#[when("I add {int} and {int}")]
 pub fn add(calc: &mut Calc, arg1: &str, arg2: &str) {
     calc.push(arg1);
     calc.push(arg2);
     calc.push("+");
 }
 
-#[given("I press (.+)")]
+#[given("^I press (.+)$")]
 pub fn press(calc: &mut Calc, what: &str) {
     calc.push(what)
 }
 
-#[then("the result is (.*)")]
+#[then("the result is {float}")]
 pub fn assert_result(calc: &mut Calc, expected: f64) {
     assert_eq!(calc.value(), expected);
 }